@@ -0,0 +1,366 @@
+//! Alerts reported through the STATUS register.
+//!
+//! [`MAX1720x::take_alerts`] reads the set alerts, clears them on the device
+//! and returns them as an [`Alerts`] set:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::alert::Alert;
+//! # fn report_soc() {}
+//! # fn shut_down() {}
+//! for alert in max17205.take_alerts(&mut i2c)? {
+//!     match alert {
+//!         Alert::SocChange => report_soc(),
+//!         Alert::VoltageLow => shut_down(),
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The ALRT pin follows the alerts once it is enabled with
+//! [`MAX1720x::set_alert_output`], at the level set with
+//! [`MAX1720x::set_alert_polarity`].
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::{Error, MAX1720x};
+
+/// An alert condition reported in the STATUS register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alert {
+    /// Current fell below the minimum alert threshold (Imn)
+    CurrentLow,
+    /// Current rose above the maximum alert threshold (Imx)
+    CurrentHigh,
+    /// State of charge changed by at least 1% (dSOCi)
+    SocChange,
+    /// Voltage fell below the minimum alert threshold (Vmn)
+    VoltageLow,
+    /// Temperature fell below the minimum alert threshold (Tmn)
+    TemperatureLow,
+    /// State of charge fell below the minimum alert threshold (Smn)
+    SocLow,
+    /// A battery was inserted (Bi)
+    BatteryInserted,
+    /// Voltage rose above the maximum alert threshold (Vmx)
+    VoltageHigh,
+    /// Temperature rose above the maximum alert threshold (Tmx)
+    TemperatureHigh,
+    /// State of charge rose above the maximum alert threshold (Smx)
+    SocHigh,
+    /// The battery was removed (Br)
+    BatteryRemoved,
+}
+
+impl Alert {
+    /// Every alert, in STATUS bit order
+    pub const ALL: [Alert; 11] = [
+        Alert::CurrentLow,
+        Alert::CurrentHigh,
+        Alert::SocChange,
+        Alert::VoltageLow,
+        Alert::TemperatureLow,
+        Alert::SocLow,
+        Alert::BatteryInserted,
+        Alert::VoltageHigh,
+        Alert::TemperatureHigh,
+        Alert::SocHigh,
+        Alert::BatteryRemoved,
+    ];
+
+    /// The bit in the STATUS register for this alert
+    pub const fn bit(self) -> u16 {
+        match self {
+            Alert::CurrentLow => 1 << 2,
+            Alert::CurrentHigh => 1 << 6,
+            Alert::SocChange => 1 << 7,
+            Alert::VoltageLow => 1 << 8,
+            Alert::TemperatureLow => 1 << 9,
+            Alert::SocLow => 1 << 10,
+            Alert::BatteryInserted => 1 << 11,
+            Alert::VoltageHigh => 1 << 12,
+            Alert::TemperatureHigh => 1 << 13,
+            Alert::SocHigh => 1 << 14,
+            Alert::BatteryRemoved => 1 << 15,
+        }
+    }
+}
+
+/// Mask of all of the alert bits in the STATUS register
+const ALERT_MASK: u16 = 0xFFC4;
+
+/// Config.Aen: drive the ALRT pin when an alert is raised
+const CONFIG_AEN: u16 = 1 << 2;
+
+/// Config.ALRTp: ALRT pin polarity
+const CONFIG_ALRTP: u16 = 1 << 11;
+
+/// The level the ALRT pin is driven to while an alert is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlertPolarity {
+    /// Driven low, the power-on setting.  The pin is open drain and needs a
+    /// pull-up.
+    #[default]
+    ActiveLow,
+    /// Driven high
+    ActiveHigh,
+}
+
+/// A set of [`Alert`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Alerts(u16);
+
+impl Alerts {
+    /// An empty set of alerts
+    pub const fn empty() -> Self {
+        Alerts(0)
+    }
+
+    /// Extract the alerts from a raw STATUS register value
+    pub const fn from_status(raw: u16) -> Self {
+        Alerts(raw & ALERT_MASK)
+    }
+
+    /// The set alerts as STATUS register bits
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    /// Whether no alerts are set
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether `alert` is in the set
+    pub const fn contains(self, alert: Alert) -> bool {
+        self.0 & alert.bit() != 0
+    }
+
+    /// Add `alert` to the set
+    pub fn insert(&mut self, alert: Alert) {
+        self.0 |= alert.bit();
+    }
+
+    /// Iterate over the alerts in the set
+    pub fn iter(self) -> AlertsIter {
+        AlertsIter {
+            alerts: self,
+            next: 0,
+        }
+    }
+}
+
+impl IntoIterator for Alerts {
+    type Item = Alert;
+    type IntoIter = AlertsIter;
+
+    fn into_iter(self) -> AlertsIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the alerts in an [`Alerts`] set
+#[derive(Debug, Clone)]
+pub struct AlertsIter {
+    alerts: Alerts,
+    next: usize,
+}
+
+impl Iterator for AlertsIter {
+    type Item = Alert;
+
+    fn next(&mut self) -> Option<Alert> {
+        while let Some(&alert) = Alert::ALL.get(self.next) {
+            self.next += 1;
+            if self.alerts.contains(alert) {
+                return Some(alert);
+            }
+        }
+        None
+    }
+}
+
+/// Alert thresholds, each given as `(min, max)`.  An alert is raised when a
+/// measurement goes outside its range; `None` disables that alert.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AlertThresholds {
+    /// Cell voltage range in volts, with 20 mV resolution
+    pub voltage: Option<(f32, f32)>,
+    /// Temperature range in degrees Celsius
+    pub temperature: Option<(i8, i8)>,
+    /// State of charge range as a percentage
+    pub soc: Option<(u8, u8)>,
+    /// Current range in amps, with 0.4 mV / Rsense resolution
+    pub current: Option<(f32, f32)>,
+}
+
+/// The threshold registers, in the order of [`AlertThresholds::from_registers`]
+const ALERT_THRESHOLD_REGISTERS: [Register; 4] = [
+    Register::VAlrtTh,
+    Register::TAlrtTh,
+    Register::SAlrtTh,
+    Register::IAlrtTh,
+];
+
+/// Pack a `(min, max)` pair of byte thresholds into a register value
+fn pack(min: u8, max: u8) -> u16 {
+    u16::from_le_bytes([min, max])
+}
+
+/// Convert a threshold to its register units, saturating at the limits
+fn scale_unsigned(value: f32, lsb: f32) -> u8 {
+    let scaled = value / lsb;
+    if scaled <= 0.0 {
+        0
+    } else if scaled >= 255.0 {
+        255
+    } else {
+        scaled as u8
+    }
+}
+
+/// Convert a signed threshold to its register units, saturating at the limits
+fn scale_signed(value: f32, lsb: f32) -> u8 {
+    let scaled = value / lsb;
+    let scaled = if scaled <= -128.0 {
+        -128
+    } else if scaled >= 127.0 {
+        127
+    } else {
+        scaled as i8
+    };
+    scaled as u8
+}
+
+/// Split a register value into its `(min, max)` pair of byte thresholds,
+/// or `None` if it spans the whole range and so is disabled
+fn unpack(raw: u16, disabled: u16) -> Option<(u8, u8)> {
+    let [min, max] = raw.to_le_bytes();
+    (raw != disabled).then_some((min, max))
+}
+
+impl AlertThresholds {
+    /// Decode the VAlrtTh, TAlrtTh, SAlrtTh and IAlrtTh register values, for
+    /// the given sense resistor value in milliohms.  A threshold pair which
+    /// spans the whole range is returned as `None`.
+    pub fn from_registers(raw: [u16; 4], rsense_milliohms: f32) -> Self {
+        let [voltage, temperature, soc, current] = raw;
+        let current_lsb = 0.4 / rsense_milliohms;
+        AlertThresholds {
+            voltage: unpack(voltage, pack(0x00, 0xFF))
+                .map(|(min, max)| (min as f32 * 0.02, max as f32 * 0.02)),
+            temperature: unpack(temperature, pack(0x80, 0x7F))
+                .map(|(min, max)| (min as i8, max as i8)),
+            soc: unpack(soc, pack(0x00, 0xFF)),
+            current: unpack(current, pack(0x80, 0x7F)).map(|(min, max)| {
+                (
+                    min as i8 as f32 * current_lsb,
+                    max as i8 as f32 * current_lsb,
+                )
+            }),
+        }
+    }
+
+    /// Register values for VAlrtTh, TAlrtTh, SAlrtTh and IAlrtTh, for the
+    /// given sense resistor value in milliohms
+    pub(crate) fn registers(&self, rsense_milliohms: f32) -> [(Register, u16); 4] {
+        let voltage = match self.voltage {
+            Some((min, max)) => pack(scale_unsigned(min, 0.02), scale_unsigned(max, 0.02)),
+            None => pack(0x00, 0xFF),
+        };
+        let temperature = match self.temperature {
+            Some((min, max)) => pack(min as u8, max as u8),
+            None => pack(0x80, 0x7F),
+        };
+        let soc = match self.soc {
+            Some((min, max)) => pack(min, max),
+            None => pack(0x00, 0xFF),
+        };
+        let current_lsb = 0.4 / rsense_milliohms;
+        let current = match self.current {
+            Some((min, max)) => pack(
+                scale_signed(min, current_lsb),
+                scale_signed(max, current_lsb),
+            ),
+            None => pack(0x80, 0x7F),
+        };
+        let [v, t, s, i] = ALERT_THRESHOLD_REGISTERS;
+        [(v, voltage), (t, temperature), (s, soc), (i, current)]
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Read the alerts from the STATUS register and clear them on the device.
+    ///
+    /// Only the alerts which were returned are cleared, but an alert raised
+    /// between the read and the clear will also be cleared without being
+    /// reported.  The POR bit is left untouched.
+    pub fn take_alerts(&mut self, bus: &mut I2C) -> Result<Alerts, Error<E>> {
+        let raw = self.read(bus, Register::Status)?;
+        let alerts = Alerts::from_status(raw);
+        if !alerts.is_empty() {
+            self.write_register(bus, Register::Status.address(), raw & !alerts.bits())?;
+        }
+        Ok(alerts)
+    }
+
+    /// Enable or disable the ALRT pin output (Config.Aen).  The alerts are
+    /// still reported in the STATUS register while the pin is disabled.
+    pub fn set_alert_output(&mut self, bus: &mut I2C, enable: bool) -> Result<(), Error<E>> {
+        let value = if enable { CONFIG_AEN } else { 0 };
+        self.modify(bus, Register::Config, CONFIG_AEN, value)
+    }
+
+    /// Set the level of the ALRT pin while an alert is set (Config.ALRTp),
+    /// to match the interrupt input it is wired to
+    pub fn set_alert_polarity(
+        &mut self,
+        bus: &mut I2C,
+        polarity: AlertPolarity,
+    ) -> Result<(), Error<E>> {
+        let value = match polarity {
+            AlertPolarity::ActiveLow => 0,
+            AlertPolarity::ActiveHigh => CONFIG_ALRTP,
+        };
+        self.modify(bus, Register::Config, CONFIG_ALRTP, value)
+    }
+
+    /// Read the voltage, temperature, state of charge and current alert
+    /// thresholds
+    pub fn alert_thresholds(&mut self, bus: &mut I2C) -> Result<AlertThresholds, Error<E>> {
+        let mut raw = [0u16; 4];
+        for (value, reg) in raw.iter_mut().zip(ALERT_THRESHOLD_REGISTERS) {
+            *value = self.read(bus, reg)?;
+        }
+        Ok(AlertThresholds::from_registers(raw, self.rsense_milliohms))
+    }
+
+    /// Set the voltage, temperature, state of charge and current alert
+    /// thresholds.  These are volatile and are reloaded from nonvolatile
+    /// memory on reset.
+    pub fn set_alert_thresholds(
+        &mut self,
+        bus: &mut I2C,
+        thresholds: &AlertThresholds,
+    ) -> Result<(), Error<E>> {
+        for (reg, value) in thresholds.registers(self.rsense_milliohms) {
+            self.write_register(bus, reg.address(), value)?;
+        }
+        Ok(())
+    }
+}
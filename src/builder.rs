@@ -0,0 +1,205 @@
+//! Builder for a configured driver.
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # use max1720x::alert::AlertThresholds;
+//! # use max1720x::builder::TemperatureSource;
+//! # use max1720x::variant::Max17205;
+//! # use max1720x::Max1720xBuilder;
+//! let mut max17205 = Max1720xBuilder::new()
+//!     .cells::<3>()
+//!     .variant::<Max17205>()
+//!     .rsense_milliohms(5.0)
+//!     .temperature_source(TemperatureSource::Thermistor1)
+//!     .alert_thresholds(AlertThresholds {
+//!         voltage: Some((3.0, 4.25)),
+//!         ..Default::default()
+//!     })
+//!     .build(&mut i2c, &mut delay)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::alert::AlertThresholds;
+use crate::config;
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::{Register, DEFAULT_RSENSE_MILLIOHMS};
+use crate::variant::{Generic, NonVolatile};
+use crate::{Cells, Error, MAX1720x, SupportedCells, TransactionStyle};
+
+/// Where the fuel gauge takes its temperature measurement from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemperatureSource {
+    /// The die temperature sensor
+    Internal,
+    /// A thermistor on AIN1
+    Thermistor1,
+    /// A thermistor on AIN1, also measuring AIN2
+    Thermistor1And2,
+}
+
+impl TemperatureSource {
+    /// The nPackCfg bits selecting this source: TdEn (bit 11), A1En
+    /// (bit 12), A2En (bit 13) and FGT (bit 15)
+    pub(crate) fn pack_cfg_bits(self) -> u16 {
+        match self {
+            TemperatureSource::Internal => (1 << 15) | (1 << 11),
+            TemperatureSource::Thermistor1 => 1 << 12,
+            TemperatureSource::Thermistor1And2 => (1 << 13) | (1 << 12),
+        }
+    }
+}
+
+/// Mask of the nPackCfg bits set from a [`TemperatureSource`]
+pub(crate) const TEMPERATURE_SOURCE_MASK: u16 = (1 << 15) | (1 << 13) | (1 << 12) | (1 << 11);
+
+/// Mask of the nPackCfg.NCELLS field
+pub(crate) const NCELLS_MASK: u16 = 0xF;
+
+/// Collects the driver options and produces a configured [`MAX1720x`].
+///
+/// The cell count and part are type parameters, as on [`MAX1720x`].
+pub struct Max1720xBuilder<const CELLS: usize = 1, V = Generic> {
+    rsense_milliohms: f32,
+    temperature_source: Option<TemperatureSource>,
+    alert_thresholds: Option<AlertThresholds>,
+    transaction_style: TransactionStyle,
+    i2c_addresses: Option<(u8, u8)>,
+    variant: PhantomData<V>,
+}
+
+impl Max1720xBuilder {
+    /// Start building a driver for a single cell pack, with a 10 mΩ sense
+    /// resistor and the device's own temperature and alert configuration
+    pub fn new() -> Self {
+        Max1720xBuilder {
+            rsense_milliohms: DEFAULT_RSENSE_MILLIOHMS,
+            temperature_source: None,
+            alert_thresholds: None,
+            transaction_style: TransactionStyle::RepeatedStart,
+            i2c_addresses: None,
+            variant: PhantomData,
+        }
+    }
+}
+
+impl Default for Max1720xBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const CELLS: usize, V> Max1720xBuilder<CELLS, V> {
+    /// Set the number of cells in series in the pack
+    pub fn cells<const N: usize>(self) -> Max1720xBuilder<N, V> {
+        Max1720xBuilder {
+            rsense_milliohms: self.rsense_milliohms,
+            temperature_source: self.temperature_source,
+            alert_thresholds: self.alert_thresholds,
+            transaction_style: self.transaction_style,
+            i2c_addresses: self.i2c_addresses,
+            variant: PhantomData,
+        }
+    }
+
+    /// Set the part, one of the markers from [`variant`](crate::variant)
+    pub fn variant<W>(self) -> Max1720xBuilder<CELLS, W> {
+        Max1720xBuilder {
+            rsense_milliohms: self.rsense_milliohms,
+            temperature_source: self.temperature_source,
+            alert_thresholds: self.alert_thresholds,
+            transaction_style: self.transaction_style,
+            i2c_addresses: self.i2c_addresses,
+            variant: PhantomData,
+        }
+    }
+
+    /// Set the sense resistor value in milliohms.  This is written to
+    /// nRSense and used to convert current and capacity readings.
+    pub fn rsense_milliohms(mut self, rsense_milliohms: f32) -> Self {
+        self.rsense_milliohms = rsense_milliohms;
+        self
+    }
+
+    /// Set where the fuel gauge takes its temperature measurement from
+    pub fn temperature_source(mut self, source: TemperatureSource) -> Self {
+        self.temperature_source = Some(source);
+        self
+    }
+
+    /// Set the alert thresholds
+    pub fn alert_thresholds(mut self, thresholds: AlertThresholds) -> Self {
+        self.alert_thresholds = Some(thresholds);
+        self
+    }
+
+    /// Set how register accesses are split into I2C transactions
+    pub fn transaction_style(mut self, style: TransactionStyle) -> Self {
+        self.transaction_style = style;
+        self
+    }
+
+    /// Set the 7-bit I2C addresses the host sees the gauge at, as
+    /// [`MAX1720x::set_i2c_addresses`]
+    pub fn i2c_addresses(mut self, lower: u8, upper: u8) -> Self {
+        self.i2c_addresses = Some((lower, upper));
+        self
+    }
+
+    /// Make the driver and apply the configuration to the device.
+    ///
+    /// The pack configuration and sense resistor are written to the shadow
+    /// RAM first.  If either changed, the fuel gauge is reset so that it
+    /// picks them up, waiting for the reset to be processed.  The alert
+    /// thresholds are written last, since a reset reloads them.  Nothing is
+    /// copied to nonvolatile memory.  Returns [`Error::Timeout`] if the
+    /// reset doesn't finish within a couple of seconds.
+    ///
+    /// The driver still needs [`MAX1720x::init`] before taking measurements.
+    pub fn build<I2C, E, D>(
+        self,
+        bus: &mut I2C,
+        delay: &mut D,
+    ) -> Result<MAX1720x<I2C, E, CELLS, V>, Error<E>>
+    where
+        I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+        D: DelayMs<u32>,
+        Cells<CELLS>: SupportedCells,
+        V: NonVolatile,
+    {
+        let mut gauge = MAX1720x::with_cells(bus);
+        gauge.set_transaction_style(self.transaction_style);
+        if let Some((lower, upper)) = self.i2c_addresses {
+            gauge.set_i2c_addresses(lower, upper);
+        }
+
+        let old_pack_cfg = gauge.read(bus, Register::NPackCfg)?;
+        let new_pack_cfg = config::pack_cfg(old_pack_cfg, CELLS, self.temperature_source);
+        let rsense = config::rsense_raw(self.rsense_milliohms);
+        let old_rsense = gauge.read(bus, Register::NRSense)?;
+
+        if new_pack_cfg != old_pack_cfg || rsense != old_rsense {
+            gauge.write_register(bus, Register::NPackCfg.address(), new_pack_cfg)?;
+            gauge.write_register(bus, Register::NRSense.address(), rsense)?;
+            gauge.reset_fuel_gauge(bus)?;
+            gauge.wait_for_reset(bus, delay)?;
+        }
+        gauge.set_rsense_milliohms(self.rsense_milliohms);
+
+        if let Some(thresholds) = &self.alert_thresholds {
+            gauge.set_alert_thresholds(bus, thresholds)?;
+        }
+        Ok(gauge)
+    }
+}
@@ -0,0 +1,107 @@
+//! Timestamps from the application's clock.
+//!
+//! The driver has no clock of its own.  Anything implementing [`Clock`],
+//! including a closure returning milliseconds, can be passed in to stamp
+//! readings with the time they were taken, so that their age can be
+//! checked later:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use core::time::Duration;
+//! # fn systick_millis() -> u64 { 0 }
+//! # fn refresh() {}
+//! let mut clock = || systick_millis();
+//! let reading = max17205.measurements_at(&mut i2c, &mut clock)?;
+//!
+//! // Later
+//! if reading.age(&mut clock) > Duration::from_secs(5) {
+//!     refresh();
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`MeasurementLog`](crate::logger::MeasurementLog) and
+//! [`SessionStats`](crate::stats::SessionStats) can take their timestamps
+//! from a [`Clock`] too.
+
+use core::time::Duration;
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::variant::PackMeasurement;
+use crate::{Error, MAX1720x, Measurements};
+
+/// A monotonic clock
+pub trait Clock {
+    /// Milliseconds since an arbitrary starting point.  Must never go
+    /// backwards.
+    fn now_ms(&mut self) -> u64;
+
+    /// The time since the starting point
+    fn now(&mut self) -> Duration {
+        Duration::from_millis(self.now_ms())
+    }
+}
+
+impl<F: FnMut() -> u64> Clock for F {
+    fn now_ms(&mut self) -> u64 {
+        self()
+    }
+}
+
+/// A value stamped with the time it was read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timestamped<T> {
+    /// When the value was read, in [`Clock`] milliseconds
+    pub at_ms: u64,
+    /// The value
+    pub value: T,
+}
+
+impl<T> Timestamped<T> {
+    /// When the value was read, as the time since the [`Clock`]'s
+    /// starting point
+    pub fn at(&self) -> Duration {
+        Duration::from_millis(self.at_ms)
+    }
+
+    /// Stamp `value` with the time now
+    pub fn now<C: Clock>(clock: &mut C, value: T) -> Self {
+        Timestamped {
+            at_ms: clock.now_ms(),
+            value,
+        }
+    }
+
+    /// How long ago the value was read
+    pub fn age<C: Clock>(&self, clock: &mut C) -> Duration {
+        Duration::from_millis(clock.now_ms().saturating_sub(self.at_ms))
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: PackMeasurement,
+{
+    /// Get the measurements, as [`measurements`](Self::measurements),
+    /// stamped with the time they were read
+    pub fn measurements_at<C: Clock>(
+        &mut self,
+        bus: &mut I2C,
+        clock: &mut C,
+    ) -> Result<Timestamped<Measurements>, Error<E>> {
+        let measurements = self.measurements(bus)?;
+        Ok(Timestamped::now(clock, measurements))
+    }
+}
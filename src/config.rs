@@ -0,0 +1,169 @@
+//! One-call configuration of the gauge for a battery.
+//!
+//! A [`BatteryConfig`] describes the pack in the terms of its datasheet, and
+//! [`MAX1720x::apply_config`] programs the nonvolatile shadow RAM, resets the
+//! fuel gauge so that it picks up the new configuration and waits for the
+//! first measurements:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # use max1720x::builder::TemperatureSource;
+//! # use max1720x::config::BatteryConfig;
+//! # use max1720x::variant::Max17205;
+//! let config = BatteryConfig {
+//!     design_capacity: 3000.0,
+//!     rsense_milliohms: 10.0,
+//!     empty_voltage: 3.0,
+//!     recovery_voltage: 3.88,
+//!     charge_termination_current: 0.15,
+//!     temperature_source: Some(TemperatureSource::Thermistor1),
+//!     alert_thresholds: None,
+//! };
+//! let mut max17205 = MAX1720x::<_, _, 2, Max17205>::with_cells(&mut i2c);
+//! max17205.apply_config(&mut i2c, &mut delay, &config, false)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The cell count is the driver's `CELLS` parameter.
+
+use crate::alert::AlertThresholds;
+use crate::builder::{TemperatureSource, NCELLS_MASK, TEMPERATURE_SOURCE_MASK};
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::{Register, Unit};
+use crate::variant::NonVolatile;
+use crate::{round, Error, MAX1720x};
+
+/// Nonvolatile registers holding the alert thresholds, in the order of
+/// [`AlertThresholds::registers`]
+const NV_ALERT_REGISTERS: [Register; 4] = [
+    Register::NVAlrtTh,
+    Register::NTAlrtTh,
+    Register::NSAlrtTh,
+    Register::NIAlrtTh,
+];
+
+/// The battery parameters needed for the gauge to report sensible values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryConfig {
+    /// Capacity of the pack from the cell datasheet, in mAh
+    pub design_capacity: f32,
+    /// Sense resistor value in milliohms
+    pub rsense_milliohms: f32,
+    /// Cell voltage at which the pack is considered empty, in volts, with
+    /// 10 mV resolution
+    pub empty_voltage: f32,
+    /// Cell voltage at which the empty detection is cleared again, in volts,
+    /// with 40 mV resolution
+    pub recovery_voltage: f32,
+    /// Charge termination current of the charger in amps
+    pub charge_termination_current: f32,
+    /// Where the temperature is measured, or `None` to leave it unchanged
+    pub temperature_source: Option<TemperatureSource>,
+    /// Alert thresholds, or `None` to leave them unchanged
+    pub alert_thresholds: Option<AlertThresholds>,
+}
+
+/// The nPackCfg value for a pack of `cells` cells, with the temperature
+/// source if given, keeping the other bits of `old`
+pub(crate) fn pack_cfg(old: u16, cells: usize, source: Option<TemperatureSource>) -> u16 {
+    let mut mask = NCELLS_MASK;
+    let mut pack_cfg = cells as u16;
+    if let Some(source) = source {
+        mask |= TEMPERATURE_SOURCE_MASK;
+        pack_cfg |= source.pack_cfg_bits();
+    }
+    (old & !mask) | pack_cfg
+}
+
+/// The nRSense value for a sense resistor in milliohms, LSB = 10 μΩ
+pub(crate) fn rsense_raw(rsense_milliohms: f32) -> u16 {
+    round(rsense_milliohms * 100.0) as u16
+}
+
+impl BatteryConfig {
+    /// The nVEmpty value: VE in bits 15:7 (10 mV) and VR in bits 6:0 (40 mV)
+    fn v_empty(&self) -> u16 {
+        // Float to integer casts saturate, so negative values become 0
+        let ve = (round(self.empty_voltage / 0.01) as u16).min(0x1FF);
+        let vr = (round(self.recovery_voltage / 0.04) as u16).min(0x7F);
+        (ve << 7) | vr
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: NonVolatile,
+{
+    /// Program the gauge for a battery and initialise the driver.
+    ///
+    /// In order, this:
+    /// 1. writes nDesignCap, nIChgTerm, nVEmpty, nPackCfg (cell count and
+    ///    temperature source), nRSense and the nonvolatile alert thresholds
+    ///    to the shadow RAM, verifying each write
+    /// 2. if `persist` is set, copies the shadow RAM to nonvolatile memory
+    ///    with [`copy_nv`](Self::copy_nv), which uses up one of the limited
+    ///    nonvolatile writes.  This is done with
+    ///    [`update_nv_registers`](Self::update_nv_registers), so nothing is
+    ///    copied if the nonvolatile memory already holds the configuration.
+    /// 3. resets the fuel gauge so that it restarts with the new
+    ///    configuration, and waits for it with
+    ///    [`init_blocking`](Self::init_blocking)
+    ///
+    /// Without `persist` the configuration is lost on the next power cycle
+    /// or hardware reset, so it must be applied again at startup.
+    pub fn apply_config<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        config: &BatteryConfig,
+        persist: bool,
+    ) -> Result<(), Error<E>> {
+        let rsense = config.rsense_milliohms;
+
+        let old_pack_cfg = self.read(bus, Register::NPackCfg)?;
+        let pack_cfg = pack_cfg(old_pack_cfg, CELLS, config.temperature_source);
+
+        let design_cap = Unit::Capacity.to_raw(config.design_capacity, rsense);
+        let ichg_term = Unit::Current.to_raw(config.charge_termination_current, rsense);
+        let mut values = [(0u16, 0u16); 5 + NV_ALERT_REGISTERS.len()];
+        values[..5].copy_from_slice(&[
+            (Register::NDesignCap.address(), design_cap),
+            (Register::NIChgTerm.address(), ichg_term),
+            (Register::NVEmpty.address(), config.v_empty()),
+            (Register::NPackCfg.address(), pack_cfg),
+            (Register::NRSense.address(), rsense_raw(rsense)),
+        ]);
+        let mut len = 5;
+        if let Some(thresholds) = &config.alert_thresholds {
+            for (reg, (_, value)) in NV_ALERT_REGISTERS.iter().zip(thresholds.registers(rsense)) {
+                values[len] = (reg.address(), value);
+                len += 1;
+            }
+        }
+        let values = &values[..len];
+
+        if persist {
+            self.update_nv_registers(bus, delay, values)?;
+        } else {
+            for &(addr, value) in values {
+                self.write_register(bus, addr, value)?;
+            }
+        }
+        // Only once the gauge has the new nRSense, so that a failed write
+        // doesn't leave the driver scaling with a value the gauge lacks
+        self.set_rsense_milliohms(rsense);
+        self.reset_fuel_gauge(bus)?;
+        self.init_blocking(bus, delay)
+    }
+}
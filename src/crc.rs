@@ -0,0 +1,56 @@
+//! CRC-16/CCITT-FALSE: polynomial 0x1021, initial value 0xFFFF.
+//!
+//! Used for the configuration checks in [`integrity`](crate::integrity) and
+//! the learned parameter blobs in [`learned`](crate::learned).
+
+/// CRC-16/CCITT polynomial
+const POLYNOMIAL: u16 = 0x1021;
+
+/// Initial CRC value
+pub(crate) const INITIAL: u16 = 0xFFFF;
+
+/// Add a byte to a CRC
+const fn update(mut crc: u16, byte: u8) -> u16 {
+    crc ^= (byte as u16) << 8;
+    let mut bit = 0;
+    while bit < 8 {
+        crc = if crc & 0x8000 != 0 {
+            (crc << 1) ^ POLYNOMIAL
+        } else {
+            crc << 1
+        };
+        bit += 1;
+    }
+    crc
+}
+
+/// Add a register value to a CRC, least significant byte first as on the
+/// bus
+pub(crate) const fn update_word(crc: u16, word: u16) -> u16 {
+    let [low, high] = word.to_le_bytes();
+    update(update(crc, low), high)
+}
+
+/// The CRC of a sequence of bytes
+pub const fn crc16(data: &[u8]) -> u16 {
+    let mut crc = INITIAL;
+    let mut i = 0;
+    while i < data.len() {
+        crc = update(crc, data[i]);
+        i += 1;
+    }
+    crc
+}
+
+/// The CRC of a sequence of register values, each least significant byte
+/// first as on the bus, as computed by
+/// [`MAX1720x::config_crc`](crate::MAX1720x::config_crc)
+pub const fn crc16_words(words: &[u16]) -> u16 {
+    let mut crc = INITIAL;
+    let mut i = 0;
+    while i < words.len() {
+        crc = update_word(crc, words[i]);
+        i += 1;
+    }
+    crc
+}
@@ -0,0 +1,197 @@
+//! Decoding raw register values without a bus.
+//!
+//! Every conversion from register contents to engineering units that the
+//! driver performs goes through these functions, so register dumps captured
+//! from the field decode exactly as they would have on the device:
+//!
+//! ```
+//! # use max1720x::decode;
+//! # use max1720x::registers::Register;
+//! # let dump = [(0x006, [0x00, 0x32])];
+//! # let rsense_milliohms = 10.0;
+//! for (addr, bytes) in dump {
+//!     if let Some(reg) = Register::from_address(addr) {
+//!         let raw = decode::word(bytes);
+//!         println!("{}: {}", reg.name(), decode::value(reg, raw, rsense_milliohms));
+//!     }
+//! }
+//! ```
+//!
+//! The flag registers decode with `from_raw`, or from their two bytes as
+//! sent on the bus with `From<[u8; 2]>` (and so `TryFrom`), for example
+//! [`Status`](crate::Status) and [`FStat`](crate::FStat).
+
+use core::time::Duration;
+
+use crate::registers::{Register, Unit};
+
+/// Assemble a register value from its two bytes in bus order, least
+/// significant byte first
+pub const fn word(bytes: [u8; 2]) -> u16 {
+    u16::from_le_bytes(bytes)
+}
+
+/// Convert a raw register value into the register's engineering units, for
+/// the given sense resistor value in milliohms.  This is
+/// [`Unit::convert`] except for the SBS ChargingCurrent and ChargingVoltage
+/// registers, which hold milliamps and millivolts and are returned in amps
+/// and volts.
+pub fn value(reg: Register, raw: u16, rsense_milliohms: f32) -> f32 {
+    match reg {
+        Register::ChargingCurrent | Register::ChargingVoltage => raw as f32 * 0.001,
+        _ => reg.unit().convert(raw, rsense_milliohms),
+    }
+}
+
+/// Check whether a raw value is the one the datasheet documents as meaning
+/// there is no data.  Only the time estimates have one: TTE, TTF and AtTTE
+/// read 0xFFFF when the estimate doesn't apply.  Every other register can
+/// hold any value, including the limits of its range.
+pub fn is_sentinel(reg: Register, raw: u16) -> bool {
+    matches!(reg, Register::TTE | Register::TTF | Register::AtTTE) && raw == 0xFFFF
+}
+
+/// Check that a raw measurement from a pack of `cells` cells could be real.
+/// Rejects the values in [`is_sentinel`], cell voltages above 5 V, and
+/// temperatures beyond ±127 °C.
+pub fn is_plausible(reg: Register, raw: u16, cells: usize) -> bool {
+    if is_sentinel(reg, raw) {
+        return false;
+    }
+    let unit = reg.unit();
+    match unit {
+        Unit::Temperature => (raw as i16).unsigned_abs() < 127 * 256,
+        Unit::CellVoltage => unit.convert(raw, 1.0) <= 5.0,
+        Unit::PackVoltage => unit.convert(raw, 1.0) <= 5.0 * cells as f32,
+        _ => true,
+    }
+}
+
+/// State of health as a percentage from raw FullCapNom and DesignCap
+/// values.  The sense resistor cancels out.  Returns 0 if DesignCap is 0.
+pub fn state_of_health(full_cap_nom: u16, design_cap: u16) -> f32 {
+    if design_cap == 0 {
+        return 0.0;
+    }
+    full_cap_nom as f32 / design_cap as f32 * 100.0
+}
+
+/// Total operating time from raw Timer and TimerH values
+pub fn uptime(timer: u16, timer_h: u16) -> Duration {
+    // Timer LSB = 175.78125 ms, TimerH LSB = 3.2 hours
+    Duration::from_secs(timer_h as u64 * 11_520) + Duration::from_nanos(timer as u64 * 175_781_250)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether `value` is within rounding error of `expected`
+    fn close(value: f32, expected: f32) -> bool {
+        (value - expected).abs() <= expected.abs() * 1e-6
+    }
+
+    #[test]
+    fn one_lsb_per_unit() {
+        // Register, value of one LSB at the default 10 mΩ sense resistor
+        let table = [
+            (Register::RepCap, 0.5),
+            (Register::RepSOC, 1.0 / 256.0),
+            (Register::VCell, 0.000_078_125),
+            (Register::Batt, 0.001_25),
+            (Register::VRipple, 0.001_25 / 128.0),
+            (Register::Current, 0.000_156_25),
+            (Register::AIN, 100.0 / 65536.0),
+            (Register::Temp, 1.0 / 256.0),
+            (Register::RCell, 1.0 / 4096.0),
+            (Register::TTE, 5.625),
+            (Register::Power, 0.0008),
+            (Register::Cycles, 0.16),
+            (Register::Status, 1.0),
+            (Register::ChargingCurrent, 0.001),
+            (Register::ChargingVoltage, 0.001),
+        ];
+        for (reg, lsb) in table {
+            let value = value(reg, 1, 10.0);
+            assert!(close(value, lsb), "{:?}: {} != {}", reg, value, lsb);
+        }
+    }
+
+    #[test]
+    fn signed_and_unsigned() {
+        // Register, raw value, value at 10 mΩ
+        let table = [
+            (Register::Current, 0xFFFF, -0.000_156_25),
+            (Register::Current, 0x8000, -32768.0 * 0.000_156_25),
+            (Register::Current, 0x7FFF, 32767.0 * 0.000_156_25),
+            (Register::Temp, 0xE700, -25.0),
+            (Register::Temp, 0x1900, 25.0),
+            (Register::Power, 0xFFFF, -0.0008),
+            (Register::RepCap, 0xFFFF, 65535.0 * 0.5),
+            (Register::VCell, 0xFFFF, 65535.0 * 0.000_078_125),
+            (Register::RepSOC, 0x8000, 128.0),
+            (Register::ChargingCurrent, 0x8000, 32.768),
+        ];
+        for (reg, raw, expected) in table {
+            let value = value(reg, raw, 10.0);
+            assert!(
+                close(value, expected),
+                "{:?} {:#06x}: {} != {}",
+                reg,
+                raw,
+                value,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rsense_scaling() {
+        // Register, value of 1000 LSBs at 5 mΩ, 10 mΩ and 20 mΩ
+        let table = [
+            (Register::RepCap, [1000.0, 500.0, 250.0]),
+            (Register::Current, [0.3125, 0.156_25, 0.078_125]),
+            (Register::Power, [1.6, 0.8, 0.4]),
+            // Not scaled by the sense resistor
+            (Register::VCell, [0.078_125; 3]),
+            (Register::RepSOC, [1000.0 / 256.0; 3]),
+            (Register::ChargingCurrent, [1.0; 3]),
+        ];
+        for (reg, expected) in table {
+            for (rsense, expected) in [5.0, 10.0, 20.0].into_iter().zip(expected) {
+                let value = value(reg, 1000, rsense);
+                assert!(
+                    close(value, expected),
+                    "{:?} at {} mΩ: {} != {}",
+                    reg,
+                    rsense,
+                    value,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sentinels() {
+        // Register, raw value, whether it marks missing data
+        let table = [
+            (Register::TTE, 0xFFFF, true),
+            (Register::TTF, 0xFFFF, true),
+            (Register::AtTTE, 0xFFFF, true),
+            (Register::TTE, 0xFFFE, false),
+            (Register::TTF, 0x0000, false),
+            (Register::Current, 0x7FFF, false),
+            (Register::Current, 0x8000, false),
+            (Register::AvgCurrent, 0x7FFF, false),
+            (Register::Power, 0x8000, false),
+            (Register::Temp, 0x7FFF, false),
+            (Register::RepCap, 0xFFFF, false),
+            (Register::VCell, 0xFFFF, false),
+            (Register::Status, 0xFFFF, false),
+        ];
+        for (reg, raw, sentinel) in table {
+            assert_eq!(is_sentinel(reg, raw), sentinel, "{:?} {:#06x}", reg, raw);
+        }
+    }
+}
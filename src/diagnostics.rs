@@ -0,0 +1,162 @@
+//! Checks for common pack wiring faults.
+//!
+//! [`MAX1720x::diagnose`] looks for the readings that wiring faults produce:
+//! a thermistor which is open or shorted pegs the temperature at one end of
+//! its range, a shorted or open sense resistor reads no current while a load
+//! is drawing it, and a disconnected cell tap sticks its voltage channel at
+//! a rail.  Production test can run it with a known load applied:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::mock::Quantity;
+//! # struct Load;
+//! # impl Load {
+//! #     fn enable(&self) {}
+//! # }
+//! # let load = Load;
+//! # i2c.set(Quantity::Current, -0.5);
+//! load.enable();
+//! let diagnosis = max17205.diagnose(&mut i2c, Some(0.5))?;
+//! assert!(diagnosis.is_ok(), "{:?}", diagnosis);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::{decode, Error, MAX1720x, CELL_REGISTERS};
+
+/// Temperatures at or below this, in degrees Celsius, mean an open
+/// thermistor
+const THERMISTOR_OPEN_CELSIUS: f32 = -40.0;
+
+/// Temperatures at or above this, in degrees Celsius, mean a shorted
+/// thermistor
+const THERMISTOR_SHORTED_CELSIUS: f32 = 100.0;
+
+/// Cell voltages below this, in volts, mean the channel is stuck at the
+/// bottom rail
+const CELL_RAIL_LOW: f32 = 0.5;
+
+/// Cell voltages at or above this, in volts, mean the channel is stuck at
+/// the top rail
+const CELL_RAIL_HIGH: f32 = 5.0;
+
+/// The fraction of a known load current which must be measured for the
+/// sense resistor to count as connected
+const MIN_LOAD_FRACTION: f32 = 0.1;
+
+/// A thermistor wiring fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThermistorFault {
+    /// The temperature is pegged cold, as for an open thermistor
+    Open,
+    /// The temperature is pegged hot, as for a shorted thermistor
+    Shorted,
+}
+
+/// A cell voltage channel stuck at a rail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellChannelFault {
+    /// The channel reads close to 0 V
+    StuckLow,
+    /// The channel reads at the top of its range
+    StuckHigh,
+}
+
+/// Faults found by [`MAX1720x::diagnose`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Diagnosis<const CELLS: usize> {
+    /// Thermistor fault, if any
+    pub thermistor: Option<ThermistorFault>,
+    /// Whether almost no current was measured under the known load, which
+    /// points to a shorted or open sense resistor.  Always false if no load
+    /// was given.
+    pub sense_resistor: bool,
+    /// Faults on each cell voltage channel, starting from the cell at the
+    /// bottom of the stack
+    pub cells: [Option<CellChannelFault>; CELLS],
+}
+
+impl<const CELLS: usize> Diagnosis<CELLS> {
+    /// Whether no faults were found
+    pub fn is_ok(&self) -> bool {
+        self.thermistor.is_none()
+            && !self.sense_resistor
+            && self.cells.iter().all(|cell| cell.is_none())
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Check the measurements for the signatures of wiring faults.
+    /// `load` is the current in amps known to be drawn from the pack while
+    /// this runs, or `None` to skip the sense resistor check.
+    ///
+    /// The raw registers are checked, so readings which validation would
+    /// reject are reported as faults rather than errors.  The thermistor
+    /// check only makes sense with a thermistor configured as the
+    /// temperature source.
+    pub fn diagnose(
+        &mut self,
+        bus: &mut I2C,
+        load: Option<f32>,
+    ) -> Result<Diagnosis<CELLS>, Error<E>> {
+        if !self.initialised {
+            return Err(Error::NotInitialised);
+        }
+
+        let temperature = self.read_converted(bus, Register::Temp)?;
+        let thermistor = if temperature <= THERMISTOR_OPEN_CELSIUS {
+            Some(ThermistorFault::Open)
+        } else if temperature >= THERMISTOR_SHORTED_CELSIUS {
+            Some(ThermistorFault::Shorted)
+        } else {
+            None
+        };
+
+        let sense_resistor = match load {
+            Some(load) => {
+                let current = self.read_converted(bus, Register::Current)?;
+                current.abs() < load.abs() * MIN_LOAD_FRACTION
+            }
+            None => false,
+        };
+
+        let mut cells = [None; CELLS];
+        for (i, cell) in cells.iter_mut().enumerate() {
+            let reg = if CELLS == 1 {
+                Register::VCell
+            } else {
+                CELL_REGISTERS[i]
+            };
+            let raw = self.read(bus, reg)?;
+            let voltage = decode::value(reg, raw, self.rsense_milliohms);
+            *cell = if voltage < CELL_RAIL_LOW {
+                Some(CellChannelFault::StuckLow)
+            } else if voltage >= CELL_RAIL_HIGH {
+                Some(CellChannelFault::StuckHigh)
+            } else {
+                None
+            };
+        }
+
+        Ok(Diagnosis {
+            thermistor,
+            sense_resistor,
+            cells,
+        })
+    }
+}
@@ -0,0 +1,126 @@
+//! Energy accounting in watt hours.
+//!
+//! [`EnergyMeter`] integrates the gauge's average power over time, keeping
+//! the energy into and out of the pack separately.  Totals are taken at
+//! checkpoints, for example once a day:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::energy::EnergyMeter;
+//! # let mut clock = || 0;
+//! # fn report<T>(_energy_in: T, _energy_out: T) {}
+//! let mut meter = EnergyMeter::new();
+//!
+//! // Every few seconds
+//! meter.update(&mut max17205, &mut i2c, &mut clock)?;
+//!
+//! // At midnight
+//! let today = meter.checkpoint();
+//! report(today.energy_in, today.energy_out);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each update integrates AvgPower over the time since the previous one, so
+//! the interval between updates should be short compared with how quickly
+//! the load changes.  [`SessionStats`](crate::stats::SessionStats) uses the
+//! same integration for the energy over a session.
+
+use core::time::Duration;
+
+use crate::clock::Clock;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::{Error, MAX1720x};
+
+/// Energy totals in watt hours, from [`EnergyMeter`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EnergyTotals {
+    /// Energy put into the pack
+    pub energy_in: f32,
+    /// Energy taken out of the pack
+    pub energy_out: f32,
+}
+
+impl EnergyTotals {
+    /// Net energy put into the pack, negative if more was taken out
+    pub fn net(&self) -> f32 {
+        self.energy_in - self.energy_out
+    }
+}
+
+/// Integrates power into energy in and out of the pack
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EnergyMeter {
+    /// Time and power of the previous sample
+    previous: Option<(Duration, f32)>,
+    totals: EnergyTotals,
+}
+
+impl EnergyMeter {
+    /// A meter with nothing counted
+    pub const fn new() -> Self {
+        EnergyMeter {
+            previous: None,
+            totals: EnergyTotals {
+                energy_in: 0.0,
+                energy_out: 0.0,
+            },
+        }
+    }
+
+    /// Add a sample of `power` in watts, positive when charging, taken at
+    /// time `at`.  The energy since the previous sample is integrated with
+    /// the trapezoidal rule; the first call only sets the starting point.
+    /// A time earlier than the previous one restarts the integration from
+    /// there without counting anything.
+    pub fn add(&mut self, at: Duration, power: f32) {
+        if let Some((previous_at, previous_power)) = self.previous {
+            let hours = at.saturating_sub(previous_at).as_secs_f32() / 3600.0;
+            let energy = (previous_power + power) / 2.0 * hours;
+            if energy > 0.0 {
+                self.totals.energy_in += energy;
+            } else {
+                self.totals.energy_out -= energy;
+            }
+        }
+        self.previous = Some((at, power));
+    }
+
+    /// Read AvgPower from a gauge and add it up to the time now
+    pub fn update<I2C, E, const CELLS: usize, V, C>(
+        &mut self,
+        gauge: &mut MAX1720x<I2C, E, CELLS, V>,
+        bus: &mut I2C,
+        clock: &mut C,
+    ) -> Result<(), Error<E>>
+    where
+        I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+        C: Clock,
+    {
+        let power = gauge.average_power(bus)?;
+        self.add(clock.now(), power);
+        Ok(())
+    }
+
+    /// The energy counted since the last checkpoint
+    pub fn totals(&self) -> EnergyTotals {
+        self.totals
+    }
+
+    /// Return the energy counted since the last checkpoint and start
+    /// counting again from zero.  The integration carries on from the last
+    /// update, so no energy is lost between checkpoints.
+    pub fn checkpoint(&mut self) -> EnergyTotals {
+        core::mem::take(&mut self.totals)
+    }
+}
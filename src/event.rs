@@ -0,0 +1,90 @@
+//! Event-driven integration using the ALRT pin.
+//!
+//! Instead of polling the gauge, enable the alerts the application cares
+//! about and call [`MAX1720x::on_alert`] when the ALRT pin is asserted, for
+//! example from the pin interrupt handler or a task it wakes:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::alert::{Alert, AlertThresholds};
+//! # struct Display;
+//! # impl Display {
+//! #     fn show_soc(&mut self, _soc: f32) {}
+//! # }
+//! # let mut display = Display;
+//! # fn shut_down() {}
+//! max17205.enable_alert_events(&mut i2c, Some(&AlertThresholds {
+//!     voltage: Some((3.0, 4.25)),
+//!     ..Default::default()
+//! }))?;
+//!
+//! // In the ALRT interrupt
+//! let event = max17205.on_alert(&mut i2c)?;
+//! display.show_soc(event.measurements.state_of_charge);
+//! if event.alerts.contains(Alert::VoltageLow) {
+//!     shut_down();
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The ALRT pin stays asserted until the alerts are cleared, which
+//! `on_alert` does, so the interrupt should be level triggered or the pin
+//! checked again afterwards.
+
+use crate::alert::{AlertThresholds, Alerts};
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::variant::PackMeasurement;
+use crate::{Error, MAX1720x, Measurements};
+
+/// What [`MAX1720x::on_alert`] found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AlertEvent {
+    /// The alerts which were raised, now cleared on the device
+    pub alerts: Alerts,
+    /// The measurements at the time of the alert
+    pub measurements: Measurements,
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Configure the gauge to assert ALRT on every 1% change in state of
+    /// charge and, if `thresholds` is given, when a measurement leaves its
+    /// threshold range
+    pub fn enable_alert_events(
+        &mut self,
+        bus: &mut I2C,
+        thresholds: Option<&AlertThresholds>,
+    ) -> Result<(), Error<E>> {
+        if let Some(thresholds) = thresholds {
+            self.set_alert_thresholds(bus, thresholds)?;
+        }
+        self.set_soc_change_alert(bus, true)
+    }
+
+    /// Handle an ALRT interrupt: read the measurements, then read and clear
+    /// the alerts which caused it
+    pub fn on_alert(&mut self, bus: &mut I2C) -> Result<AlertEvent, Error<E>>
+    where
+        V: PackMeasurement,
+    {
+        let measurements = self.measurements(bus)?;
+        let alerts = self.take_alerts(bus)?;
+        Ok(AlertEvent {
+            alerts,
+            measurements,
+        })
+    }
+}
@@ -0,0 +1,118 @@
+//! Lean raw reads for high rate polling.
+//!
+//! The normal getters check the driver state, convert to floating point and
+//! work out the bus addresses on every call.  For control loops polling at
+//! kHz rates the raw readers here do one bus transaction with the register
+//! bytes resolved at compile time, and return the register contents as
+//! integers:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::fast;
+//! // In a 1 kHz interrupt
+//! let raw = max17205.current_raw(&mut i2c)?;
+//! let microamps = fast::current_microamps(raw, 10_000);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! They skip the initialisation check, validation and tracing, so check
+//! that the driver is initialised before starting the loop.  The gauge only
+//! updates Current every 175.8 ms; faster polling returns the same value
+//! until the next update.
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::variant::PackMeasurement;
+use crate::{reg_addr, Error, MAX1720x, TransactionStyle};
+
+/// A register with its register byte resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Resolved {
+    addr: u16,
+    reg: u8,
+}
+
+impl Resolved {
+    const fn new(reg: Register) -> Self {
+        Resolved {
+            addr: reg.address(),
+            reg: reg_addr(reg.address()),
+        }
+    }
+}
+
+const CURRENT: Resolved = Resolved::new(Register::Current);
+const AVG_CURRENT: Resolved = Resolved::new(Register::AvgCurrent);
+const VCELL: Resolved = Resolved::new(Register::VCell);
+const BATT: Resolved = Resolved::new(Register::Batt);
+
+/// Convert a raw Current or AvgCurrent value into microamps, for a sense
+/// resistor of `rsense_microohms`.  LSB = 1.5625 μV / Rsense.
+pub const fn current_microamps(raw: i16, rsense_microohms: u32) -> i32 {
+    (raw as i64 * 1_562_500 / rsense_microohms as i64) as i32
+}
+
+/// Convert a raw VCell or Cell1 - Cell4 value into millivolts.  LSB =
+/// 78.125 μV.
+pub const fn cell_millivolts(raw: u16) -> u16 {
+    (raw as u32 * 5 / 64) as u16
+}
+
+/// Convert a raw Batt value into millivolts.  LSB = 1.25 mV.
+pub const fn pack_millivolts(raw: u16) -> u32 {
+    raw as u32 * 5 / 4
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Read the raw instantaneous current, from Current
+    pub fn current_raw(&mut self, bus: &mut I2C) -> Result<i16, Error<E>> {
+        Ok(self.read_resolved(bus, CURRENT)? as i16)
+    }
+
+    /// Read the raw average current, from AvgCurrent
+    pub fn avg_current_raw(&mut self, bus: &mut I2C) -> Result<i16, Error<E>> {
+        Ok(self.read_resolved(bus, AVG_CURRENT)? as i16)
+    }
+
+    /// Read the raw lowest cell voltage, from VCell
+    pub fn cell_voltage_raw(&mut self, bus: &mut I2C) -> Result<u16, Error<E>> {
+        self.read_resolved(bus, VCELL)
+    }
+
+    /// Read the raw pack voltage, from Batt
+    pub fn pack_voltage_raw(&mut self, bus: &mut I2C) -> Result<u16, Error<E>>
+    where
+        V: PackMeasurement,
+    {
+        self.read_resolved(bus, BATT)
+    }
+
+    fn read_resolved(&mut self, bus: &mut I2C, resolved: Resolved) -> Result<u16, Error<E>> {
+        let device = self.device_addr(resolved.addr);
+        let mut raw = [0u8; 2];
+        match self.transaction_style {
+            TransactionStyle::RepeatedStart => bus
+                .write_read(device, &[resolved.reg], &mut raw)
+                .map_err(Error::I2c)?,
+            TransactionStyle::Separate => {
+                bus.write(device, &[resolved.reg]).map_err(Error::I2c)?;
+                bus.read(device, &mut raw).map_err(Error::I2c)?;
+            }
+        }
+        Ok(u16::from_le_bytes(raw))
+    }
+}
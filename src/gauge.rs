@@ -0,0 +1,109 @@
+//! A fuel gauge interface independent of the part.
+//!
+//! Application code written against [`FuelGauge`] can run on any gauge which
+//! implements it, and be tested against a stub.  The driver methods borrow
+//! the bus on every call, so [`MAX1720x::attach`] pairs the driver with a
+//! bus to give a [`FuelGauge`]:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::gauge::FuelGauge;
+//! fn report<G: FuelGauge>(gauge: &mut G) -> Result<(), G::Error> {
+//!     println!("{}% {}V", gauge.state_of_charge()?, gauge.voltage()?);
+//!     Ok(())
+//! }
+//!
+//! report(&mut max17205.attach(&mut i2c))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use core::time::Duration;
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::variant::PackMeasurement;
+use crate::{Error, MAX1720x};
+
+/// The measurements common to battery fuel gauges
+pub trait FuelGauge {
+    /// Error returned by the gauge
+    type Error;
+
+    /// State of charge as a percentage
+    fn state_of_charge(&mut self) -> Result<f32, Self::Error>;
+
+    /// Pack voltage in volts
+    fn voltage(&mut self) -> Result<f32, Self::Error>;
+
+    /// Pack current in amps, positive when charging
+    fn current(&mut self) -> Result<f32, Self::Error>;
+
+    /// Pack temperature in degrees Celsius
+    fn temperature(&mut self) -> Result<f32, Self::Error>;
+
+    /// Estimated time until the pack is empty, or `None` while it is not
+    /// discharging
+    fn time_to_empty(&mut self) -> Result<Option<Duration>, Self::Error>;
+}
+
+/// A [`MAX1720x`] together with the bus it is on, returned by
+/// [`MAX1720x::attach`]
+pub struct Attached<'a, I2C, E, const CELLS: usize, V> {
+    gauge: &'a mut MAX1720x<I2C, E, CELLS, V>,
+    bus: &'a mut I2C,
+}
+
+impl<I2C, E, const CELLS: usize, V> Attached<'_, I2C, E, CELLS, V> {
+    /// The driver and bus, for calls outside of [`FuelGauge`]
+    pub fn parts(&mut self) -> (&mut MAX1720x<I2C, E, CELLS, V>, &mut I2C) {
+        (self.gauge, self.bus)
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> FuelGauge for Attached<'_, I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: PackMeasurement,
+{
+    type Error = Error<E>;
+
+    fn state_of_charge(&mut self) -> Result<f32, Error<E>> {
+        self.gauge.state_of_charge(self.bus)
+    }
+
+    fn voltage(&mut self) -> Result<f32, Error<E>> {
+        self.gauge.pack_voltage(self.bus)
+    }
+
+    fn current(&mut self) -> Result<f32, Error<E>> {
+        self.gauge.current(self.bus)
+    }
+
+    fn temperature(&mut self) -> Result<f32, Error<E>> {
+        self.gauge.temperature(self.bus)
+    }
+
+    fn time_to_empty(&mut self) -> Result<Option<Duration>, Error<E>> {
+        self.gauge.time_to_empty(self.bus)
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Pair the driver with its bus, to use it as a [`FuelGauge`]
+    pub fn attach<'a>(&'a mut self, bus: &'a mut I2C) -> Attached<'a, I2C, E, CELLS, V> {
+        Attached { gauge: self, bus }
+    }
+}
@@ -0,0 +1,107 @@
+//! How the coulomb counter and voltage fuel gauge are mixed.
+//!
+//! ModelGauge m5 continuously corrects the coulomb count towards the state
+//! of charge estimated from the cell voltage.  For chemistries with a flat
+//! voltage curve, such as LiFePO4 and LTO, the voltage estimate is poor
+//! over most of the range and the corrections do more harm than good.
+//! [`GaugingMode::CoulombCounter`] makes the coulomb count dominant:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::gauging::GaugingMode;
+//! max17205.set_gauging_mode(&mut i2c, GaugingMode::CoulombCounter)?;
+//! max17205.copy_nv(&mut i2c, &mut delay)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The mode is set with two fields: MiscCfg.MR, the rate at which the
+//! estimates are servoed together, and FilterCfg.MIX, the time constant of
+//! the mixing.  Coulomb counter errors are then only corrected very
+//! slowly, so an accurate sense resistor value and regular full charges
+//! matter more.
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::variant::NonVolatile;
+use crate::{Error, MAX1720x};
+
+/// Mask of MiscCfg.MR, bits 9:5
+const MISC_CFG_MR_MASK: u16 = 0x1F << 5;
+
+/// Mask of FilterCfg.MIX, bits 10:7
+const FILTER_CFG_MIX_MASK: u16 = 0xF << 7;
+
+/// How the coulomb counter and voltage fuel gauge are mixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GaugingMode {
+    /// The power-on defaults: servo mixing at MR = 3 and a 12.8 hour mixing
+    /// time constant
+    Standard,
+    /// Servo mixing disabled and the longest mixing time constant, 51 hours,
+    /// so that the state of charge follows the coulomb counter
+    CoulombCounter,
+}
+
+impl GaugingMode {
+    /// The (MiscCfg, FilterCfg) bits for this mode
+    fn bits(self) -> (u16, u16) {
+        match self {
+            GaugingMode::Standard => (3 << 5, 13 << 7),
+            GaugingMode::CoulombCounter => (0, 15 << 7),
+        }
+    }
+
+    /// The mode matching MiscCfg and FilterCfg values, if any
+    fn from_bits(misc_cfg: u16, filter_cfg: u16) -> Option<Self> {
+        let bits = (
+            misc_cfg & MISC_CFG_MR_MASK,
+            filter_cfg & FILTER_CFG_MIX_MASK,
+        );
+        [GaugingMode::Standard, GaugingMode::CoulombCounter]
+            .into_iter()
+            .find(|mode| mode.bits() == bits)
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Get the gauging mode in use, from MiscCfg and FilterCfg.  Returns
+    /// `None` if the fields have been set to something other than one of
+    /// the [`GaugingMode`]s.
+    pub fn gauging_mode(&mut self, bus: &mut I2C) -> Result<Option<GaugingMode>, Error<E>> {
+        let misc_cfg = self.read(bus, Register::MiscCfg)?;
+        let filter_cfg = self.read(bus, Register::FilterCfg)?;
+        Ok(GaugingMode::from_bits(misc_cfg, filter_cfg))
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: NonVolatile,
+{
+    /// Set the gauging mode.  It is written both to MiscCfg and FilterCfg,
+    /// taking effect immediately, and to nMiscCfg and nFilterCfg in the
+    /// shadow RAM, so that [`copy_nv`](Self::copy_nv) makes it permanent.
+    /// The other fields of the registers are left alone.
+    pub fn set_gauging_mode(&mut self, bus: &mut I2C, mode: GaugingMode) -> Result<(), Error<E>> {
+        let (misc_cfg, filter_cfg) = mode.bits();
+        self.modify(bus, Register::MiscCfg, MISC_CFG_MR_MASK, misc_cfg)?;
+        self.modify(bus, Register::FilterCfg, FILTER_CFG_MIX_MASK, filter_cfg)?;
+        self.modify(bus, Register::NMiscCfg, MISC_CFG_MR_MASK, misc_cfg)?;
+        self.modify(bus, Register::NFilterCfg, FILTER_CFG_MIX_MASK, filter_cfg)
+    }
+}
@@ -0,0 +1,246 @@
+//! Per-cell undervoltage protection.
+//!
+//! [`UndervoltageGuard`] holds a minimum voltage for each cell and checks
+//! the cell voltages against them, on a timer or when ALRT is asserted.
+//! Each breach identifies the cell, so load disconnect logic can act on it:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # use max1720x::guard::UndervoltageGuard;
+//! # use max1720x::variant::Max17205;
+//! # let mut max17205 = MAX1720x::<_, _, 3, Max17205>::with_cells(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # fn log_breach(_cell: usize, _voltage: f32) {}
+//! # fn disconnect_load() {}
+//! let guard = UndervoltageGuard::new([3.0, 3.0, 2.9]);
+//!
+//! guard.check_each(&mut max17205, &mut i2c, |violation| {
+//!     log_breach(violation.cell, violation.voltage);
+//!     disconnect_load();
+//! })?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The cell voltages are read coherently with
+//! [`MAX1720x::cell_voltages`].  The gauge's own voltage alert only sees the
+//! lowest cell (VCell) against a single threshold, so setting it to the
+//! lowest limit and checking here on ALRT avoids polling.
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::variant::PackMeasurement;
+use crate::{Error, MAX1720x};
+
+/// A cell below its minimum voltage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellViolation {
+    /// Index of the cell, counting from the bottom of the stack
+    pub cell: usize,
+    /// The cell voltage in volts
+    pub voltage: f32,
+    /// The minimum voltage for the cell in volts
+    pub limit: f32,
+}
+
+/// Minimum voltages for each cell of a `CELLS` cell pack
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UndervoltageGuard<const CELLS: usize> {
+    limits: [f32; CELLS],
+}
+
+impl<const CELLS: usize> UndervoltageGuard<CELLS> {
+    /// A guard with a minimum voltage in volts for each cell, starting from
+    /// the cell at the bottom of the stack
+    pub const fn new(limits: [f32; CELLS]) -> Self {
+        UndervoltageGuard { limits }
+    }
+
+    /// A guard with the same minimum voltage for every cell
+    pub const fn uniform(limit: f32) -> Self {
+        UndervoltageGuard {
+            limits: [limit; CELLS],
+        }
+    }
+
+    /// The minimum voltage for each cell
+    pub fn limits(&self) -> &[f32; CELLS] {
+        &self.limits
+    }
+
+    /// Iterate over the cells in `voltages` which are below their limits
+    pub fn violations<'a>(
+        &'a self,
+        voltages: &'a [f32; CELLS],
+    ) -> impl Iterator<Item = CellViolation> + 'a {
+        voltages
+            .iter()
+            .zip(self.limits.iter())
+            .enumerate()
+            .filter(|(_, (voltage, limit))| voltage < limit)
+            .map(|(cell, (&voltage, &limit))| CellViolation {
+                cell,
+                voltage,
+                limit,
+            })
+    }
+
+    /// The cell furthest below its limit, if any
+    pub fn worst(&self, voltages: &[f32; CELLS]) -> Option<CellViolation> {
+        self.violations(voltages)
+            .fold(None, |worst, violation| match worst {
+                Some(w) if w.limit - w.voltage >= violation.limit - violation.voltage => Some(w),
+                _ => Some(violation),
+            })
+    }
+
+    /// Read the cell voltages from a gauge and return the cell furthest
+    /// below its limit, if any
+    pub fn check<I2C, E, V>(
+        &self,
+        gauge: &mut MAX1720x<I2C, E, CELLS, V>,
+        bus: &mut I2C,
+    ) -> Result<Option<CellViolation>, Error<E>>
+    where
+        I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+        V: PackMeasurement,
+    {
+        let voltages = gauge.cell_voltages(bus)?;
+        Ok(self.worst(&voltages))
+    }
+
+    /// Read the cell voltages from a gauge and call `on_violation` for each
+    /// cell below its limit.  Returns the number of cells which were.
+    pub fn check_each<I2C, E, V, F>(
+        &self,
+        gauge: &mut MAX1720x<I2C, E, CELLS, V>,
+        bus: &mut I2C,
+        mut on_violation: F,
+    ) -> Result<usize, Error<E>>
+    where
+        I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+        V: PackMeasurement,
+        F: FnMut(CellViolation),
+    {
+        let voltages = gauge.cell_voltages(bus)?;
+        let mut count = 0;
+        for violation in self.violations(&voltages) {
+            on_violation(violation);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockError, MockMax1720x, Quantity};
+    use crate::registers::Register;
+
+    const CELLS: [Register; 4] = [
+        Register::Cell1,
+        Register::Cell2,
+        Register::Cell3,
+        Register::Cell4,
+    ];
+
+    /// A mock pack with the given cell voltages and an initialised driver
+    fn pack<const N: usize>(
+        voltages: [f32; N],
+    ) -> (MockMax1720x, MAX1720x<MockMax1720x, MockError, N>)
+    where
+        crate::Cells<N>: crate::SupportedCells,
+    {
+        let mut mock = MockMax1720x::new();
+        for (reg, voltage) in CELLS.iter().zip(voltages) {
+            let raw = reg.unit().to_raw(voltage, 10.0);
+            mock.set_register(reg.address(), raw);
+        }
+        let mut gauge = MAX1720x::with_cells(&mut mock);
+        gauge.init(&mut mock).unwrap();
+        (mock, gauge)
+    }
+
+    #[test]
+    fn undervoltage() {
+        let (mut mock, mut gauge) = pack([3.7, 2.9, 3.6]);
+        let guard = UndervoltageGuard::uniform(3.0);
+        let violation = guard.check(&mut gauge, &mut mock).unwrap().unwrap();
+        assert_eq!(violation.cell, 1);
+        assert_eq!(violation.limit, 3.0);
+        assert!((violation.voltage - 2.9).abs() < 0.001);
+    }
+
+    #[test]
+    fn overvoltage_is_not_a_violation() {
+        let (mut mock, mut gauge) = pack([4.5, 4.9, 4.3]);
+        let guard = UndervoltageGuard::uniform(3.0);
+        assert_eq!(guard.check(&mut gauge, &mut mock), Ok(None));
+        assert_eq!(guard.check_each(&mut gauge, &mut mock, |_| panic!()), Ok(0));
+    }
+
+    #[test]
+    fn imbalance_uses_each_cells_limit() {
+        // Cell 0 is the lowest but within its own limit; cell 3 is further
+        // below its limit than cell 2
+        let (mut mock, mut gauge) = pack([2.8, 3.4, 3.25, 3.3]);
+        let guard = UndervoltageGuard::new([2.7, 3.0, 3.3, 3.5]);
+        let worst = guard.check(&mut gauge, &mut mock).unwrap().unwrap();
+        assert_eq!(worst.cell, 3);
+        assert_eq!(worst.limit, 3.5);
+    }
+
+    #[test]
+    fn callback_for_each_violation() {
+        let (mut mock, mut gauge) = pack([3.1, 2.5, 3.6, 2.9]);
+        let guard = UndervoltageGuard::uniform(3.2);
+        let mut cells = [usize::MAX; 4];
+        let count = guard
+            .check_each(&mut gauge, &mut mock, |violation| {
+                cells[violation.cell] = violation.cell;
+                assert_eq!(violation.limit, 3.2);
+            })
+            .unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(cells, [0, 1, usize::MAX, 3]);
+    }
+
+    #[test]
+    fn single_cell_reads_vcell() {
+        let mut mock = MockMax1720x::new();
+        let mut gauge = MAX1720x::new(&mut mock);
+        gauge.init(&mut mock).unwrap();
+        // Cell1 disagrees, so a violation shows VCell was used
+        mock.set(Quantity::CellVoltage, 3.7);
+        mock.set_register(
+            Register::VCell.address(),
+            Register::VCell.unit().to_raw(2.8, 10.0),
+        );
+        let guard = UndervoltageGuard::uniform(3.0);
+        let violation = guard.check(&mut gauge, &mut mock).unwrap().unwrap();
+        assert_eq!(violation.cell, 0);
+        assert!((violation.voltage - 2.8).abs() < 0.001);
+
+        mock.set(Quantity::CellVoltage, 3.7);
+        assert_eq!(guard.check(&mut gauge, &mut mock), Ok(None));
+    }
+
+    #[test]
+    fn read_errors_are_returned() {
+        let (mut mock, mut gauge) = pack([3.7, 3.7]);
+        let guard = UndervoltageGuard::uniform(3.0);
+        mock.fail_next(1);
+        assert_eq!(
+            guard.check_each(&mut gauge, &mut mock, |_| panic!()),
+            Err(Error::I2c(MockError::Injected))
+        );
+    }
+}
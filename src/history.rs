@@ -0,0 +1,296 @@
+//! Nonvolatile battery history.
+//!
+//! The gauge periodically saves a snapshot of its learned parameters and the
+//! extremes seen by the pack into nonvolatile memory.  Each snapshot is
+//! recalled one at a time into registers 0x1E0 - 0x1EF using the Command
+//! register, then read out and decoded as a [`HistoryEntry`].
+//!
+//! Which pages hold history is recorded in two sets of flags, recalled the
+//! same way: a page's write flag is set once it has been written, and its
+//! valid flag once the write completed.  Pages are written in order, so the
+//! first page without a write flag marks the end of the history, and pages
+//! written without a valid flag are skipped.
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! for entry in max17205.history(&mut i2c, &mut delay) {
+//!     let entry = entry?;
+//!     println!("{} cycles, {} mAh", entry.cycles(), entry.full_capacity_nominal());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::variant::NonVolatile;
+use crate::{decode, Error, MAX1720x};
+
+/// Number of history pages stored by the device
+pub const HISTORY_PAGES: u8 = 203;
+
+/// Command which recalls history page 0.  Page N is recalled by adding N.
+const CMD_RECALL_HISTORY: u16 = 0xE226;
+
+/// Register that recalled history pages are copied to
+const HISTORY_START: u16 = 0x1E0;
+
+/// Time taken to recall a page from nonvolatile memory (tRECALL), in ms
+const T_RECALL_MS: u32 = 5;
+
+/// Number of flag words in each of the write and valid flag sets, each
+/// covering 8 pages
+const FLAG_WORDS: usize = 26;
+
+/// Where the history flag words are recalled to, in order: the command, the
+/// first register and the number of words for each part of the write flags
+/// followed by the valid flags
+const FLAG_RECALLS: [(u16, u16, usize); 5] = [
+    (0xE2FB, 0x1E1, 15),
+    (0xE2FC, 0x1E0, 11),
+    (0xE2FC, 0x1EB, 5),
+    (0xE2FD, 0x1E0, 16),
+    (0xE2FE, 0x1E0, 5),
+];
+
+/// The write and valid flags for every history page.  Each flag word holds
+/// the flags for 8 pages twice over, in bits 0 - 7 and 8 - 15, and a flag
+/// counts as set if either copy is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HistoryFlags {
+    write: [u16; FLAG_WORDS],
+    valid: [u16; FLAG_WORDS],
+}
+
+impl HistoryFlags {
+    fn is_set(words: &[u16; FLAG_WORDS], page: u8) -> bool {
+        let word = words[page as usize / 8];
+        let bit = page % 8;
+        word & ((1 << bit) | (1 << (bit + 8))) != 0
+    }
+
+    /// Whether the page has been written
+    fn is_written(&self, page: u8) -> bool {
+        Self::is_set(&self.write, page)
+    }
+
+    /// Whether the write to the page completed
+    fn is_valid(&self, page: u8) -> bool {
+        Self::is_set(&self.valid, page)
+    }
+}
+
+/// One snapshot from the battery history, laid out like the learned
+/// parameter registers 0x1A0 - 0x1AF.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoryEntry {
+    words: [u16; 16],
+    rsense_milliohms: f32,
+}
+
+impl HistoryEntry {
+    /// Make an entry from a raw history page, for example from a register
+    /// dump, to be decoded for the given sense resistor value in milliohms
+    pub fn from_raw(words: [u16; 16], rsense_milliohms: f32) -> Self {
+        HistoryEntry {
+            words,
+            rsense_milliohms,
+        }
+    }
+
+    /// The raw history page, in the order of registers 0x1A0 - 0x1AF
+    pub fn raw(&self) -> &[u16; 16] {
+        &self.words
+    }
+
+    /// Charge cycle count
+    pub fn cycles(&self) -> f32 {
+        self.convert(Register::NCycles)
+    }
+
+    /// Nominal full capacity in mAh
+    pub fn full_capacity_nominal(&self) -> f32 {
+        self.convert(Register::NFullCapNom)
+    }
+
+    /// Reported full capacity in mAh
+    pub fn full_capacity(&self) -> f32 {
+        self.convert(Register::NFullCapRep)
+    }
+
+    /// Temperature compensation reference resistance (raw RComp0)
+    pub fn rcomp0(&self) -> u16 {
+        self.word(Register::NRComp0)
+    }
+
+    /// Temperature compensation coefficients (raw TempCo)
+    pub fn temp_co(&self) -> u16 {
+        self.word(Register::NTempCo)
+    }
+
+    /// Maximum and minimum temperature seen, in degrees Celsius
+    pub fn temperature_range(&self) -> (f32, f32) {
+        let [min, max] = self.word(Register::NMaxMinTemp).to_le_bytes();
+        ((max as i8) as f32, (min as i8) as f32)
+    }
+
+    /// Maximum and minimum cell voltage seen, in volts (LSB = 20 mV)
+    pub fn voltage_range(&self) -> (f32, f32) {
+        let [min, max] = self.word(Register::NMaxMinVolt).to_le_bytes();
+        (max as f32 * 0.02, min as f32 * 0.02)
+    }
+
+    /// Maximum and minimum current seen, in amps (LSB = 0.4 mV / Rsense)
+    pub fn current_range(&self) -> (f32, f32) {
+        let [min, max] = self.word(Register::NMaxMinCurr).to_le_bytes();
+        let lsb = 0.4 / self.rsense_milliohms;
+        ((max as i8) as f32 * lsb, (min as i8) as f32 * lsb)
+    }
+
+    /// Total elapsed time in hours (LSB = 3.2 hours)
+    pub fn elapsed_hours(&self) -> f32 {
+        self.word(Register::NTimerH) as f32 * 3.2
+    }
+
+    fn word(&self, reg: Register) -> u16 {
+        self.words[(reg.address() - Register::NQRTable00.address()) as usize]
+    }
+
+    fn convert(&self, reg: Register) -> f32 {
+        decode::value(reg, self.word(reg), self.rsense_milliohms)
+    }
+}
+
+/// Iterator over the history entries stored in the device, returned by
+/// [`MAX1720x::history`]
+pub struct HistoryEntries<'a, I2C, E, D, const CELLS: usize, V> {
+    gauge: &'a mut MAX1720x<I2C, E, CELLS, V>,
+    bus: &'a mut I2C,
+    delay: &'a mut D,
+    flags: Option<HistoryFlags>,
+    page: u8,
+}
+
+impl<I2C, E, D, const CELLS: usize, V> HistoryEntries<'_, I2C, E, D, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u32>,
+{
+    /// Recall a page of nonvolatile memory into 0x1E0 - 0x1EF
+    fn recall(&mut self, command: u16) -> Result<(), Error<E>> {
+        self.gauge
+            .write_register(self.bus, Register::Command.address(), command)?;
+        self.delay.delay_ms(T_RECALL_MS);
+        Ok(())
+    }
+
+    /// Recall the write and valid flags
+    fn read_flags(&mut self) -> Result<HistoryFlags, Error<E>> {
+        let mut words = [0u16; 2 * FLAG_WORDS];
+        let mut filled = 0;
+        for (command, start, len) in FLAG_RECALLS {
+            self.recall(command)?;
+            self.gauge.dump_registers(
+                self.bus,
+                start..start + len as u16,
+                &mut words[filled..filled + len],
+            )?;
+            filled += len;
+        }
+        let mut flags = HistoryFlags {
+            write: [0; FLAG_WORDS],
+            valid: [0; FLAG_WORDS],
+        };
+        flags.write.copy_from_slice(&words[..FLAG_WORDS]);
+        flags.valid.copy_from_slice(&words[FLAG_WORDS..]);
+        Ok(flags)
+    }
+
+    /// Recall and read one history page
+    fn read_page(&mut self, page: u8) -> Result<[u16; 16], Error<E>> {
+        self.recall(CMD_RECALL_HISTORY + page as u16)?;
+        let mut words = [0u16; 16];
+        self.gauge
+            .dump_registers(self.bus, HISTORY_START..HISTORY_START + 16, &mut words)?;
+        Ok(words)
+    }
+}
+
+impl<I2C, E, D, const CELLS: usize, V> Iterator for HistoryEntries<'_, I2C, E, D, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    D: DelayMs<u32>,
+{
+    type Item = Result<HistoryEntry, Error<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let flags = match self.flags {
+            Some(flags) => flags,
+            None => match self.read_flags() {
+                Ok(flags) => *self.flags.insert(flags),
+                Err(e) => {
+                    self.page = HISTORY_PAGES;
+                    return Some(Err(e));
+                }
+            },
+        };
+
+        loop {
+            // Pages are written in order, so the first unwritten page marks
+            // the end of the history
+            if self.page >= HISTORY_PAGES || !flags.is_written(self.page) {
+                self.page = HISTORY_PAGES;
+                return None;
+            }
+            let page = self.page;
+            self.page += 1;
+            if !flags.is_valid(page) {
+                continue;
+            }
+
+            return Some(match self.read_page(page) {
+                Ok(words) => Ok(HistoryEntry::from_raw(words, self.gauge.rsense_milliohms)),
+                Err(e) => {
+                    self.page = HISTORY_PAGES;
+                    Err(e)
+                }
+            });
+        }
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: NonVolatile,
+{
+    /// Iterate over the battery history stored in nonvolatile memory, oldest
+    /// first.  The history flags are recalled on the first call to `next`,
+    /// then each entry is recalled as the iterator advances, which takes a
+    /// few milliseconds.  Entries are decoded with the driver's sense
+    /// resistor value.  Iteration stops after the first error.
+    pub fn history<'a, D: DelayMs<u32>>(
+        &'a mut self,
+        bus: &'a mut I2C,
+        delay: &'a mut D,
+    ) -> HistoryEntries<'a, I2C, E, D, CELLS, V> {
+        HistoryEntries {
+            gauge: self,
+            bus,
+            delay,
+            flags: None,
+            page: 0,
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Pack identification stored in nonvolatile memory.
+//!
+//! The manufacturer and device names are reported to the host through the
+//! SBS ManufacturerName and DeviceName registers, and are normally
+//! programmed during production:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! max17205.set_manufacturer_name(&mut i2c, "ACME")?;
+//! max17205.set_device_name(&mut i2c, "PACK-2S")?;
+//! max17205.copy_nv(&mut i2c, &mut delay)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The setters only write the shadow RAM, so that several fields can be
+//! stored with a single [`MAX1720x::copy_nv`].
+//!
+//! Each pack can also be given a 48-bit serial number.  Every part has a
+//! unique factory programmed ROM ID as well, which
+//! [`MAX1720x::pack_identity`] falls back to for packs without a serial
+//! number.
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::variant::NonVolatile;
+use crate::{Error, MAX1720x};
+
+/// Maximum length of the manufacturer name in bytes
+pub const MANUFACTURER_NAME_LEN: usize = 6;
+
+/// Maximum length of the device name in bytes
+pub const DEVICE_NAME_LEN: usize = 10;
+
+/// Registers holding the manufacturer name, two characters each
+const MANUFACTURER_NAME: [Register; MANUFACTURER_NAME_LEN / 2] = [
+    Register::NManfctrName0,
+    Register::NManfctrName1,
+    Register::NManfctrName2,
+];
+
+/// Registers holding the device name, two characters each
+const DEVICE_NAME: [Register; DEVICE_NAME_LEN / 2] = [
+    Register::NDeviceName0,
+    Register::NDeviceName1,
+    Register::NDeviceName2,
+    Register::NDeviceName3,
+    Register::NDeviceName4,
+];
+
+/// Registers holding the serial number, least significant word first
+const SERIAL_NUMBER: [Register; 3] = [
+    Register::NSerialNumber0,
+    Register::NSerialNumber1,
+    Register::NSerialNumber2,
+];
+
+/// Registers holding the ROM ID, least significant word first
+const ROM_ID: [Register; 4] = [
+    Register::NRomID0,
+    Register::NRomID1,
+    Register::NRomID2,
+    Register::NRomID3,
+];
+
+/// Largest serial number which fits in nSerialNumber
+pub const MAX_SERIAL_NUMBER: u64 = (1 << 48) - 1;
+
+/// How a pack identifies itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackIdentity {
+    /// The serial number programmed into the pack
+    SerialNumber(u64),
+    /// The factory programmed ROM ID of the gauge, for packs without a
+    /// serial number
+    RomId(u64),
+}
+
+/// A fixed-length ASCII name read from nonvolatile memory, padded with
+/// zero bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Name<const N: usize> {
+    bytes: [u8; N],
+}
+
+impl<const N: usize> Name<N> {
+    /// The stored bytes, including any padding
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.bytes
+    }
+
+    /// The name up to the first zero byte, or `None` if it isn't valid
+    /// UTF-8, as in a part which was never programmed
+    pub fn as_str(&self) -> Option<&str> {
+        let len = self.bytes.iter().position(|&b| b == 0).unwrap_or(N);
+        core::str::from_utf8(&self.bytes[..len]).ok()
+    }
+}
+
+/// Pack an ASCII name into little-endian words, padding with zero bytes.
+/// Returns `None` if the name is too long or isn't ASCII.
+fn encode_name<const W: usize>(name: &str) -> Option<[u16; W]> {
+    if name.len() > W * 2 || !name.is_ascii() {
+        return None;
+    }
+    let mut words = [0u16; W];
+    for (word, chunk) in words.iter_mut().zip(name.as_bytes().chunks(2)) {
+        *word = u16::from_le_bytes([chunk[0], chunk.get(1).copied().unwrap_or(0)]);
+    }
+    Some(words)
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: NonVolatile,
+{
+    /// Read the manufacturer name from nManfctrName
+    pub fn manufacturer_name(
+        &mut self,
+        bus: &mut I2C,
+    ) -> Result<Name<MANUFACTURER_NAME_LEN>, Error<E>> {
+        let mut bytes = [0u8; MANUFACTURER_NAME_LEN];
+        self.read_name(bus, &MANUFACTURER_NAME, &mut bytes)?;
+        Ok(Name { bytes })
+    }
+
+    /// Write the manufacturer name, up to 6 ASCII characters, to
+    /// nManfctrName in the shadow RAM and verify it.  Returns
+    /// [`Error::InvalidArgument`] if the name doesn't fit.
+    pub fn set_manufacturer_name(&mut self, bus: &mut I2C, name: &str) -> Result<(), Error<E>> {
+        let words: [u16; MANUFACTURER_NAME_LEN / 2] =
+            encode_name(name).ok_or(Error::InvalidArgument)?;
+        self.write_verified(bus, &MANUFACTURER_NAME, &words)
+    }
+
+    /// Read the device name from nDeviceName
+    pub fn device_name(&mut self, bus: &mut I2C) -> Result<Name<DEVICE_NAME_LEN>, Error<E>> {
+        let mut bytes = [0u8; DEVICE_NAME_LEN];
+        self.read_name(bus, &DEVICE_NAME, &mut bytes)?;
+        Ok(Name { bytes })
+    }
+
+    /// Write the device name, up to 10 ASCII characters, to nDeviceName in
+    /// the shadow RAM and verify it.  Returns [`Error::InvalidArgument`] if
+    /// the name doesn't fit.
+    pub fn set_device_name(&mut self, bus: &mut I2C, name: &str) -> Result<(), Error<E>> {
+        let words: [u16; DEVICE_NAME_LEN / 2] = encode_name(name).ok_or(Error::InvalidArgument)?;
+        self.write_verified(bus, &DEVICE_NAME, &words)
+    }
+
+    /// Read the serial number from nSerialNumber
+    pub fn serial_number(&mut self, bus: &mut I2C) -> Result<u64, Error<E>> {
+        self.read_u64(bus, &SERIAL_NUMBER)
+    }
+
+    /// Write the serial number to nSerialNumber in the shadow RAM and verify
+    /// it.  Returns [`Error::InvalidArgument`] if it is larger than
+    /// [`MAX_SERIAL_NUMBER`].
+    pub fn set_serial_number(&mut self, bus: &mut I2C, serial: u64) -> Result<(), Error<E>> {
+        if serial > MAX_SERIAL_NUMBER {
+            return Err(Error::InvalidArgument);
+        }
+        let words = [serial as u16, (serial >> 16) as u16, (serial >> 32) as u16];
+        self.write_verified(bus, &SERIAL_NUMBER, &words)
+    }
+
+    /// Read the gauge's unique 64-bit ROM ID from nROMID
+    pub fn rom_id(&mut self, bus: &mut I2C) -> Result<u64, Error<E>> {
+        self.read_u64(bus, &ROM_ID)
+    }
+
+    /// Identify the pack by its serial number, or by the gauge ROM ID if no
+    /// serial number has been programmed (all zeros or all ones)
+    pub fn pack_identity(&mut self, bus: &mut I2C) -> Result<PackIdentity, Error<E>> {
+        let serial = self.serial_number(bus)?;
+        if serial != 0 && serial != MAX_SERIAL_NUMBER {
+            return Ok(PackIdentity::SerialNumber(serial));
+        }
+        Ok(PackIdentity::RomId(self.rom_id(bus)?))
+    }
+
+    /// Read registers as one little-endian number
+    fn read_u64(&mut self, bus: &mut I2C, regs: &[Register]) -> Result<u64, Error<E>> {
+        let mut value = 0;
+        for (i, reg) in regs.iter().enumerate() {
+            value |= (self.read(bus, *reg)? as u64) << (16 * i);
+        }
+        Ok(value)
+    }
+
+    fn read_name(
+        &mut self,
+        bus: &mut I2C,
+        regs: &[Register],
+        bytes: &mut [u8],
+    ) -> Result<(), Error<E>> {
+        for (chunk, reg) in bytes.chunks_mut(2).zip(regs) {
+            chunk.copy_from_slice(&self.read(bus, *reg)?.to_le_bytes());
+        }
+        Ok(())
+    }
+}
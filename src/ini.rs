@@ -0,0 +1,145 @@
+//! Import configurations exported by the Maxim evaluation kit software.
+//!
+//! The EV kit software saves the device configuration as an INI file with
+//! one register per line, keyed either by register name or by address:
+//!
+//! ```text
+//! [Device=MAX17205]
+//! nPackCfg = 0x3C02
+//! 0x1B3 = 0x1770
+//! 1CF = 03E8
+//! ```
+//!
+//! [`NvImage::from_ini`] turns this into an [`NvImage`] which can be written
+//! with [`MAX1720x::restore_config`](crate::MAX1720x::restore_config) or
+//! [`MAX1720x::provision`](crate::MAX1720x::provision):
+//!
+//! ```no_run
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::nv::NvImage;
+//! let image = NvImage::from_ini(&std::fs::read_to_string("pack.ini")?)?;
+//! max17205.provision(&mut i2c, &mut delay, &image, false)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Section headers, comments starting with `;` or `#`, and keys which aren't
+//! registers (such as a title) are skipped, as are registers outside the
+//! nonvolatile configuration.  Addresses and values are always hexadecimal,
+//! as the EV kit writes them, with or without a `0x` prefix or an `h`
+//! suffix: `1B3`, `0x1B3` and `1B3h` are all the same.  Decimal values
+//! aren't accepted.
+
+use std::fmt;
+
+use crate::nv::{NvImage, NV_IMAGE_WORDS};
+use crate::registers::Register;
+
+/// A line of an INI file which names a register but has an invalid value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IniError {
+    /// Line number, starting from 1
+    pub line: usize,
+}
+
+impl fmt::Display for IniError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid register value on line {}", self.line)
+    }
+}
+
+impl std::error::Error for IniError {}
+
+/// Parse a hexadecimal number in any of the formats used by the EV kit
+/// software
+fn parse_number(text: &str) -> Option<u16> {
+    let hex = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .or_else(|| text.strip_suffix('h'))
+        .or_else(|| text.strip_suffix('H'))
+        .unwrap_or(text);
+    u16::from_str_radix(hex, 16).ok()
+}
+
+/// Find the register address a key refers to
+fn parse_key(key: &str) -> Option<u16> {
+    Register::from_name(key)
+        .map(Register::address)
+        .or_else(|| parse_number(key))
+}
+
+impl NvImage {
+    /// Make an image from an EV kit INI file.  Registers which aren't in the
+    /// file are left as zero, so the file should cover the whole nonvolatile
+    /// configuration; use [`NvImage::apply_ini`] to start from a backup
+    /// instead.
+    pub fn from_ini(text: &str) -> Result<Self, IniError> {
+        let mut image = NvImage::from_words([0; NV_IMAGE_WORDS]);
+        image.apply_ini(text)?;
+        Ok(image)
+    }
+
+    /// Overwrite the registers listed in an EV kit INI file, leaving the
+    /// rest of the image unchanged
+    pub fn apply_ini(&mut self, text: &str) -> Result<(), IniError> {
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split([';', '#']).next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let Some(addr) = parse_key(key.trim()) else {
+                continue;
+            };
+            let value = parse_number(value.trim()).ok_or(IniError { line: i + 1 })?;
+            self.set(addr, value);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_with_or_without_prefix() {
+        let image = NvImage::from_ini(
+            "[Device=MAX17205]\n\
+             nPackCfg = 0x3C02\n\
+             0x1B3 = 1770\n\
+             1CF = 03E8h\n\
+             1b0 = 0X00ff ; comment\n",
+        )
+        .unwrap();
+        assert_eq!(image.get(Register::NPackCfg.address()), Some(0x3c02));
+        assert_eq!(image.get(0x1b3), Some(0x1770));
+        assert_eq!(image.get(Register::NRSense.address()), Some(0x03e8));
+        assert_eq!(image.get(0x1b0), Some(0x00ff));
+    }
+
+    #[test]
+    fn invalid_value_reports_line() {
+        assert_eq!(
+            NvImage::from_ini("nPackCfg = 0x3C02\nnRSense = 10mR\n"),
+            Err(IniError { line: 2 })
+        );
+        assert_eq!(
+            NvImage::from_ini("nPackCfg = 12345\n"),
+            Err(IniError { line: 1 })
+        );
+    }
+}
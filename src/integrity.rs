@@ -0,0 +1,110 @@
+//! Detecting silent corruption of the configuration.
+//!
+//! A brown-out or a disturbed bus can change the gauge configuration without
+//! any error being reported.  Take the CRC of the configuration once it has
+//! been applied, keep it, and check the configuration against it
+//! periodically:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::config::BatteryConfig;
+//! # use max1720x::integrity::CONFIG_REGISTERS;
+//! # use max1720x::Error;
+//! # let config = BatteryConfig {
+//! #     design_capacity: 3000.0,
+//! #     rsense_milliohms: 10.0,
+//! #     empty_voltage: 3.0,
+//! #     recovery_voltage: 3.88,
+//! #     charge_termination_current: 0.15,
+//! #     temperature_source: None,
+//! #     alert_thresholds: None,
+//! # };
+//! # fn reapply_config() {}
+//! max17205.apply_config(&mut i2c, &mut delay, &config, false)?;
+//! let crc = max17205.config_crc(&mut i2c, &CONFIG_REGISTERS)?;
+//!
+//! // Periodically
+//! match max17205.verify_config(&mut i2c, &CONFIG_REGISTERS, crc) {
+//!     Err(Error::ConfigCrcMismatch(_)) => reapply_config(),
+//!     result => result?,
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The expected CRC can also be worked out ahead of time from the register
+//! values with [`crc16_words`].  Measurements are checked with
+//! [`MAX1720x::set_redundant_reads`].
+
+use crate::crc::{update_word, INITIAL};
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::{Error, MAX1720x};
+
+/// The shadow RAM registers holding the configuration written by
+/// [`MAX1720x::apply_config`](crate::MAX1720x::apply_config) and the
+/// builder, plus the gauge's other operating settings
+pub const CONFIG_REGISTERS: [Register; 15] = [
+    Register::NDesignCap,
+    Register::NIChgTerm,
+    Register::NVEmpty,
+    Register::NPackCfg,
+    Register::NRSense,
+    Register::NConfig,
+    Register::NFilterCfg,
+    Register::NMiscCfg,
+    Register::NLearnCfg,
+    Register::NConvgCfg,
+    Register::NTempCo,
+    Register::NVAlrtTh,
+    Register::NTAlrtTh,
+    Register::NSAlrtTh,
+    Register::NIAlrtTh,
+];
+
+/// The CRC-16/CCITT-FALSE of a sequence of register values, as computed by
+/// [`MAX1720x::config_crc`]
+pub use crate::crc::crc16_words;
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Read the registers `regs`, such as [`CONFIG_REGISTERS`], and return
+    /// the CRC of their values in order.  With redundant reads enabled each
+    /// register is checked as it is read.
+    pub fn config_crc(&mut self, bus: &mut I2C, regs: &[Register]) -> Result<u16, Error<E>> {
+        let mut crc = INITIAL;
+        for &reg in regs {
+            crc = update_word(crc, self.read_checked(bus, reg)?);
+        }
+        Ok(crc)
+    }
+
+    /// Read the registers `regs` back and check them against the CRC
+    /// `expected`, returning [`Error::ConfigCrcMismatch`] if they don't
+    /// match
+    pub fn verify_config(
+        &mut self,
+        bus: &mut I2C,
+        regs: &[Register],
+        expected: u16,
+    ) -> Result<(), Error<E>> {
+        let crc = self.config_crc(bus, regs)?;
+        if crc == expected {
+            Ok(())
+        } else {
+            Err(Error::ConfigCrcMismatch(crc))
+        }
+    }
+}
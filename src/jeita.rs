@@ -0,0 +1,201 @@
+//! Temperature-aware charge advice.
+//!
+//! Lithium-ion cells must be charged more gently when cool or warm and not
+//! at all outside that.  JEITA splits the temperature range into zones,
+//! each with its own charge current and voltage.  [`ChargeProfile`] holds
+//! the zones and limits, and [`MAX1720x::charge_advice`] applies them to the
+//! measured temperature and pack voltage:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::jeita::ChargeProfile;
+//! # struct Charger;
+//! # impl Charger {
+//! #     fn set_current(&mut self, _amps: f32) {}
+//! #     fn set_voltage(&mut self, _volts: f32) {}
+//! # }
+//! # let mut charger = Charger;
+//! let profile = ChargeProfile {
+//!     warm_limit: 50.0,
+//!     ..ChargeProfile::jeita(2.0, 4.2)
+//! };
+//!
+//! let advice = max17205.charge_advice(&mut i2c, &profile)?;
+//! charger.set_current(advice.current);
+//! charger.set_voltage(advice.voltage);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The advice is only as fresh as the measurements, so it should be updated
+//! at least every few seconds while charging.
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::variant::PackMeasurement;
+use crate::{Error, MAX1720x};
+
+/// A JEITA temperature zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemperatureZone {
+    /// Too cold to charge
+    Cold,
+    /// Charge at reduced current
+    Cool,
+    /// Charge normally
+    Standard,
+    /// Charge to a reduced voltage
+    Warm,
+    /// Too hot to charge
+    Hot,
+}
+
+/// The charge current and voltage for a temperature zone
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZoneLimits {
+    /// Charge current in amps
+    pub current: f32,
+    /// Charge voltage per cell in volts
+    pub voltage: f32,
+}
+
+/// Temperature zones and the charge limits in each
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChargeProfile {
+    /// Below this temperature, in degrees Celsius, the pack is cold
+    pub cold_limit: f32,
+    /// Below this temperature, in degrees Celsius, the pack is cool
+    pub cool_limit: f32,
+    /// Above this temperature, in degrees Celsius, the pack is warm
+    pub warm_limit: f32,
+    /// Above this temperature, in degrees Celsius, the pack is hot
+    pub hot_limit: f32,
+    /// Limits in the cool zone
+    pub cool: ZoneLimits,
+    /// Limits in the standard zone
+    pub standard: ZoneLimits,
+    /// Limits in the warm zone
+    pub warm: ZoneLimits,
+    /// Below this cell voltage, in volts, the cells are deeply discharged
+    /// and charged at the precharge current
+    pub precharge_voltage: f32,
+    /// Charge current in amps while precharging
+    pub precharge_current: f32,
+}
+
+impl ChargeProfile {
+    /// The standard JEITA profile for a charge current in amps and a charge
+    /// voltage per cell in volts: cold below 0 °C, cool up to 10 °C at half
+    /// current, warm above 45 °C at 100 mV less, and hot above 60 °C.
+    /// Cells below 3 V are precharged at a tenth of the charge current.
+    pub const fn jeita(current: f32, voltage: f32) -> Self {
+        ChargeProfile {
+            cold_limit: 0.0,
+            cool_limit: 10.0,
+            warm_limit: 45.0,
+            hot_limit: 60.0,
+            cool: ZoneLimits {
+                current: current * 0.5,
+                voltage,
+            },
+            standard: ZoneLimits { current, voltage },
+            warm: ZoneLimits {
+                current,
+                voltage: voltage - 0.1,
+            },
+            precharge_voltage: 3.0,
+            precharge_current: current * 0.1,
+        }
+    }
+
+    /// The zone for a temperature in degrees Celsius
+    pub fn zone(&self, temperature: f32) -> TemperatureZone {
+        if temperature < self.cold_limit {
+            TemperatureZone::Cold
+        } else if temperature < self.cool_limit {
+            TemperatureZone::Cool
+        } else if temperature <= self.warm_limit {
+            TemperatureZone::Standard
+        } else if temperature <= self.hot_limit {
+            TemperatureZone::Warm
+        } else {
+            TemperatureZone::Hot
+        }
+    }
+
+    /// The advice for a pack of `cells` cells at a temperature in degrees
+    /// Celsius and pack voltage in volts
+    pub fn advise(&self, temperature: f32, pack_voltage: f32, cells: usize) -> ChargeAdvice {
+        let zone = self.zone(temperature);
+        let limits = match zone {
+            TemperatureZone::Cold | TemperatureZone::Hot => ZoneLimits {
+                current: 0.0,
+                voltage: 0.0,
+            },
+            TemperatureZone::Cool => self.cool,
+            TemperatureZone::Standard => self.standard,
+            TemperatureZone::Warm => self.warm,
+        };
+        let cells = cells as f32;
+        let precharge = limits.current > 0.0 && pack_voltage / cells < self.precharge_voltage;
+        let current = if precharge {
+            limits.current.min(self.precharge_current)
+        } else {
+            limits.current
+        };
+        ChargeAdvice {
+            zone,
+            precharge,
+            current,
+            voltage: limits.voltage * cells,
+        }
+    }
+}
+
+/// The recommended charge settings, from [`ChargeProfile::advise`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChargeAdvice {
+    /// The temperature zone
+    pub zone: TemperatureZone,
+    /// Whether the cells are deeply discharged and being precharged
+    pub precharge: bool,
+    /// Charge current in amps, 0 if the pack mustn't be charged
+    pub current: f32,
+    /// Charge voltage for the pack in volts, 0 if the pack mustn't be
+    /// charged
+    pub voltage: f32,
+}
+
+impl ChargeAdvice {
+    /// Whether the pack may be charged at all
+    pub fn may_charge(&self) -> bool {
+        self.current > 0.0
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: PackMeasurement,
+{
+    /// Read the temperature and pack voltage and work out the charge
+    /// current and voltage from `profile`
+    pub fn charge_advice(
+        &mut self,
+        bus: &mut I2C,
+        profile: &ChargeProfile,
+    ) -> Result<ChargeAdvice, Error<E>> {
+        let temperature = self.temperature(bus)?;
+        let pack_voltage = self.pack_voltage(bus)?;
+        Ok(profile.advise(temperature, pack_voltage, CELLS))
+    }
+}
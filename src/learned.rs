@@ -0,0 +1,184 @@
+//! Checkpointing the learned cell parameters.
+//!
+//! The gauge learns the capacity and characteristics of the cells as they
+//! are used.  A host without a nonvolatile configuration to save them to
+//! can checkpoint them into its own flash and restore them after the gauge
+//! loses power:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::learned::{LearnedParams, LEARNED_PARAMS_LEN};
+//! # struct Flash(Vec<u8>);
+//! # impl Flash {
+//! #     fn write(&mut self, offset: usize, data: &[u8]) {
+//! #         self.0[offset..offset + data.len()].copy_from_slice(data);
+//! #     }
+//! #     fn read(&self, offset: usize, len: usize) -> Vec<u8> {
+//! #         self.0[offset..offset + len].to_vec()
+//! #     }
+//! # }
+//! # let mut flash = Flash(vec![0; 256]);
+//! # const PARAMS_OFFSET: usize = 0;
+//! let params = max17205.learned_params(&mut i2c)?;
+//! flash.write(PARAMS_OFFSET, &params.to_bytes());
+//!
+//! // After power loss
+//! if let Ok(params) = LearnedParams::from_bytes(&flash.read(PARAMS_OFFSET, LEARNED_PARAMS_LEN)) {
+//!     max17205.restore_learned_params(&mut i2c, &params)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The serialised form starts with a format version byte and ends with a
+//! CRC-16, so corrupted or incompatible checkpoints are refused rather than
+//! loaded into the gauge.
+
+use core::fmt;
+
+use crate::crc::crc16;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::{Error, MAX1720x, TemperatureCompensation};
+
+/// The learned registers, in the order they are restored
+const LEARNED_REGISTERS: [Register; 11] = [
+    Register::RComp0,
+    Register::TempCo,
+    Register::FullCapRep,
+    Register::Cycles,
+    Register::FullCapNom,
+    Register::DQAcc,
+    Register::DPAcc,
+    Register::QRTable00,
+    Register::QRTable10,
+    Register::QRTable20,
+    Register::QRTable30,
+];
+
+/// Version of the serialised format written by [`LearnedParams::to_bytes`]
+pub const LEARNED_PARAMS_VERSION: u8 = 1;
+
+/// Size of a serialised [`LearnedParams`] in bytes: the version, two bytes
+/// per register and the CRC
+pub const LEARNED_PARAMS_LEN: usize = 1 + LEARNED_REGISTERS.len() * 2 + 2;
+
+/// Why a serialised [`LearnedParams`] was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobError {
+    /// The data is not [`LEARNED_PARAMS_LEN`] bytes long
+    Length,
+    /// The data was written by an incompatible version of the format
+    Version(u8),
+    /// The CRC doesn't match, so the data is corrupted
+    Crc,
+}
+
+impl fmt::Display for BlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlobError::Length => write!(f, "learned parameters have the wrong length"),
+            BlobError::Version(v) => write!(f, "unsupported learned parameters version {}", v),
+            BlobError::Crc => write!(f, "learned parameters CRC mismatch"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BlobError {}
+
+/// A snapshot of the learned cell parameters: RComp0, TempCo, FullCapRep,
+/// Cycles, FullCapNom, dQAcc, dPAcc and the QRTable registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LearnedParams {
+    words: [u16; LEARNED_REGISTERS.len()],
+}
+
+impl LearnedParams {
+    /// The value of a learned register, or `None` if the register isn't
+    /// one of the learned parameters
+    pub fn get(&self, reg: Register) -> Option<u16> {
+        LEARNED_REGISTERS
+            .iter()
+            .position(|&r| r == reg)
+            .map(|i| self.words[i])
+    }
+
+    /// The temperature compensation parameters
+    pub fn temperature_compensation(&self) -> TemperatureCompensation {
+        TemperatureCompensation {
+            rcomp0: self.words[0],
+            temp_co: self.words[1],
+        }
+    }
+
+    /// Serialise the parameters: the format version, each register
+    /// little-endian, then the CRC of everything before it, little-endian
+    pub fn to_bytes(&self) -> [u8; LEARNED_PARAMS_LEN] {
+        let mut bytes = [0u8; LEARNED_PARAMS_LEN];
+        bytes[0] = LEARNED_PARAMS_VERSION;
+        for (chunk, word) in bytes[1..].chunks_mut(2).zip(self.words.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        let crc = crc16(&bytes[..LEARNED_PARAMS_LEN - 2]);
+        bytes[LEARNED_PARAMS_LEN - 2..].copy_from_slice(&crc.to_le_bytes());
+        bytes
+    }
+
+    /// Deserialise parameters produced by [`LearnedParams::to_bytes`],
+    /// checking the length, version and CRC
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BlobError> {
+        if bytes.len() != LEARNED_PARAMS_LEN {
+            return Err(BlobError::Length);
+        }
+        if bytes[0] != LEARNED_PARAMS_VERSION {
+            return Err(BlobError::Version(bytes[0]));
+        }
+        let (data, crc) = bytes.split_at(LEARNED_PARAMS_LEN - 2);
+        if crc16(data) != u16::from_le_bytes([crc[0], crc[1]]) {
+            return Err(BlobError::Crc);
+        }
+        let mut words = [0u16; LEARNED_REGISTERS.len()];
+        for (word, chunk) in words.iter_mut().zip(data[1..].chunks(2)) {
+            *word = u16::from_le_bytes([chunk[0], chunk[1]]);
+        }
+        Ok(Self { words })
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Read the learned cell parameters
+    pub fn learned_params(&mut self, bus: &mut I2C) -> Result<LearnedParams, Error<E>> {
+        Ok(LearnedParams {
+            words: self.read_coherent(bus, LEARNED_REGISTERS)?,
+        })
+    }
+
+    /// Restore learned cell parameters saved with
+    /// [`learned_params`](Self::learned_params), after the gauge has been
+    /// reset and [`init`](Self::init) has succeeded.  Only restore
+    /// parameters saved from the same pack.
+    pub fn restore_learned_params(
+        &mut self,
+        bus: &mut I2C,
+        params: &LearnedParams,
+    ) -> Result<(), Error<E>> {
+        for (reg, word) in LEARNED_REGISTERS.iter().zip(params.words) {
+            self.write_register(bus, reg.address(), word)?;
+        }
+        Ok(())
+    }
+}
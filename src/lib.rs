@@ -13,6 +13,21 @@
 //! > cell pack. The MAX17205/MAX17215 monitor and balance a 2S or 3S pack or
 //! > monitor a multiple-series cell pack.
 //!
+//! Features
+//! --------
+//!
+//! - `std`: implements `std::error::Error` for [`Error`], so driver errors
+//!   can be used with `anyhow`, `thiserror` and `?` in `main` on Linux hosts
+//!   such as the Raspberry Pi.  Also adds [`ini`], for importing
+//!   configurations exported by the Maxim evaluation kit software, and
+//!   [`report`], for printing the gauge state as text.
+//! - `logger`: adds [`logger`], a fixed-capacity ring buffer of periodic
+//!   measurements with summary statistics.
+//! - `mock`: adds [`mock::MockMax1720x`], a simulated device for testing
+//!   application code on the host without hardware.
+//! - `trace`: adds [`trace`], a hook called with every register read and
+//!   write.  Without it the hook and its calls are compiled out.
+//!
 //! Usage
 //! -----
 //!
@@ -20,27 +35,128 @@
 //! fn main() {
 //!     let mut i2c = I2c::new().unwrap();
 //!     let mut max17205 = MAX1720x::new(&mut i2c);
+//!     max17205.init(&mut i2c).unwrap();
 //!     let soc = max17205.state_of_charge(&mut i2c).unwrap();
 //!     let status = max17205.status(&mut i2c).unwrap();
-//!     let voltage = max17205.voltage(&mut i2c).unwrap();
+//!     let voltage = max17205.pack_voltage(&mut i2c).unwrap();
 //!     let current = max17205.current(&mut i2c).unwrap();
 //!     println!("State of charge: {}%", soc);
 //!     println!("Voltage: {}V", voltage);
 //!     println!("Current: {}A", current);
 //!     println!("Status: {:#?}", status);
 //! }
+//!
+//! Measurements are only available once [`MAX1720x::init`] has succeeded,
+//! which checks that the gauge has finished its first measurements after a
+//! reset.  Until then the measurement methods return
+//! [`Error::NotInitialised`].
+//!
+//! Consistency
+//! -----------
+//!
+//! The gauge updates its registers independently of the bus, so two
+//! separate register reads may straddle an update.  Methods which combine
+//! several registers, such as [`MAX1720x::cell_voltages`] and
+//! [`MAX1720x::state_of_health`], re-read the whole set until two reads in a
+//! row agree, so the values they return come from the same update.  If the
+//! registers keep changing they return [`Error::Inconsistent`].  Separate
+//! method calls are not coherent with each other.
+//!
+//! Sharing the bus
+//! ---------------
+//!
+//! The driver never owns the bus: every method borrows it for the duration
+//! of the call.  Any type implementing the embedded-hal I2C traits can be
+//! passed in, so bus-sharing wrappers such as the proxies from `shared-bus`
+//! work without any adapter:
+//!
+//! ```ignore
+//! let manager = shared_bus::BusManagerSimple::new(i2c);
+//! let mut gauge_bus = manager.acquire_i2c();
+//! let mut max17205 = MAX1720x::new(&mut gauge_bus);
+//! max17205.init(&mut gauge_bus)?;
+//! let soc = max17205.state_of_charge(&mut gauge_bus)?;
+//! ```
+//!
+//! A proxy can be kept alongside the driver for the lifetime of the
+//! application, or a fresh one acquired for each access.  Multi-register
+//! operations such as [`MAX1720x::dump_registers`] are split into several
+//! bus transactions, so another device may be accessed between them; this
+//! is harmless as the gauge holds no state between transactions.
+//!
+//! The MAX1720x answers on two I2C addresses: 0x36 for registers 0x000 -
+//! 0x0FF and 0x0B for registers 0x100 - 0x1FF.  Both must be free on a
+//! shared bus.  0x0B is also the Smart Battery System address, so it clashes
+//! with any other SBS battery on the same bus.  Behind an address
+//! translator the addresses the host sees can be set with
+//! [`MAX1720x::set_i2c_addresses`].
 
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Range;
+use core::time::Duration;
 use embedded_hal as hal;
+use hal::blocking::delay::DelayMs;
 use hal::blocking::i2c::{Read, Write, WriteRead};
-use core::marker::PhantomData;
+
+pub mod alert;
+pub mod builder;
+pub mod clock;
+pub mod config;
+pub mod crc;
+pub mod decode;
+pub mod diagnostics;
+pub mod energy;
+pub mod event;
+pub mod fast;
+pub mod gauge;
+pub mod gauging;
+pub mod guard;
+pub mod history;
+pub mod identity;
+#[cfg(feature = "std")]
+pub mod ini;
+pub mod integrity;
+pub mod jeita;
+pub mod learned;
+#[cfg(feature = "logger")]
+pub mod logger;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod mux;
+pub mod nonblocking;
+pub mod nv;
+pub mod por;
+pub mod protector;
+pub mod provision;
+pub mod rates;
+pub mod registers;
+#[cfg(feature = "std")]
+pub mod report;
+pub mod revision;
+pub mod sampling;
+pub mod session;
+pub mod shutdown;
+pub mod stats;
+pub mod swap;
+#[cfg(feature = "trace")]
+pub mod trace;
+pub mod variant;
+pub mod warning;
+
+pub use builder::Max1720xBuilder;
+pub use nb;
+use registers::{Register, Unit, DEFAULT_RSENSE_MILLIOHMS};
+use revision::Feature;
+use variant::{Balancing, Generic, NonVolatile, PackMeasurement, SmartBattery, Variant};
 
 // Addresses 0x000 - 0x0FF, 0x180 - 0x1FF can be written as blocks
 // Addresses 0x100 - 0x17F must be written by word
 
-// Addresses 0x000 - 0x0FF should use the ADDR_LOWER address
-// Addresses 0x100 - 0x1FF should use the ADDR_UPPER address
+// By default addresses 0x000 - 0x0FF use the ADDR_LOWER address
+// and addresses 0x100 - 0x1FF use the ADDR_UPPER address
 
 // Note that the datasheet gives addresses in 0-bit format with the RW bit
 // set to 0.  We want the addresses in 7-bit format, i.e. the datasheet ones
@@ -48,96 +164,182 @@ use core::marker::PhantomData;
 const ADDR_LOWER: u8 = 0x36;
 const ADDR_UPPER: u8 = 0x0b;
 
-#[allow(dead_code)]
-#[repr(u16)]
-enum Registers {
-    Status = 0x000,     // Status flags
-    RepCap = 0x005,     // Reported capacity, LSB = 0.5 mAh
-    RepSOC = 0x006,     // Reported capacity, LSB = %/256
-    Voltage = 0x009,    // The lowest reading from all cell voltages, LSB = 0.078125 mV
-    Current = 0x00A,    // Instantaneous current, LSB = 156.25 uA
-    Tte = 0x011,        // Time To Empty
-    Ttf = 0x020,        // Time to Full
-    FullCapRep = 0x035, // Maximum capacity, LSB = 0.5 mAh
-    Coulomb = 0x04D,    // Raw coloumb count
-    Batt = 0x0DA,       // Pack voltage, LSB = 1.25mV
-    NPackCfg = 0x1B5,   // Pack configuration
-    NRomID = 0x1BC,     // RomID - 64bit unique
-    NRSense = 0x1CF,    // Sense resistor
-}
-
-/// Return the I2C device address used to communicate when accessing this
-/// register
-fn device_addr(reg: Registers) -> u8 {
-    if reg as u16 > 0x100 {
-        ADDR_UPPER
-    } else {
-        ADDR_LOWER
-    }
+/// Maximum number of registers read in one I2C transaction
+const BLOCK_WORDS: usize = 16;
+
+/// Interval between polls while waiting for the device, in ms
+const POLL_INTERVAL_MS: u32 = 10;
+
+/// Time to wait for the first measurements after a reset, in ms
+const DATA_READY_TIMEOUT_MS: u32 = 2_000;
+
+/// Time to wait for the device to come back after a hardware reset (tPOR),
+/// in ms
+const T_POR_MS: u32 = 10;
+
+/// Time to wait for the device to respond after a hardware reset, in ms
+const RESET_TIMEOUT_MS: u32 = 100;
+
+/// Command register value for a hardware reset
+const CMD_HARDWARE_RESET: u16 = 0x000F;
+
+/// Highest register address in the device memory map
+const MAX_REGISTER: u16 = 0x1FF;
+
+/// Number of times a write to the shadow RAM is attempted before giving up
+/// on verifying it
+const CONFIG_WRITE_ATTEMPTS: usize = 3;
+
+/// Number of times a set of registers is re-read looking for two matching
+/// reads
+const COHERENT_READ_ATTEMPTS: usize = 3;
+
+/// Return the register address used to access this register address
+const fn reg_addr(addr: u16) -> u8 {
+    (addr & 0xff) as u8
+}
+
+/// Errors returned by the driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The underlying I2C bus returned an error
+    I2c(E),
+    /// The register address is outside the device memory map
+    InvalidRegister(u16),
+    /// The register did not hold the value written to it when read back
+    VerifyFailed(u16),
+    /// A measurement was requested before [`MAX1720x::init`] succeeded
+    NotInitialised,
+    /// The gauge has not finished its first measurements after reset
+    DataNotReady,
+    /// The device did not finish an operation in time
+    Timeout,
+    /// The device reported an error copying to nonvolatile memory
+    NvCopyFailed,
+    /// An argument was outside the range the device can store
+    InvalidArgument,
+    /// The register kept changing while reading a consistent set of
+    /// registers
+    Inconsistent(u16),
+    /// The nonvolatile memory can't be written any more times
+    NvUpdatesExhausted,
+    /// With validation enabled, the register held a physically implausible
+    /// value
+    SuspectReading(u16),
+    /// The register held a value which the gauge uses to mark missing data,
+    /// such as 0xFFFF, or which its configuration makes meaningless
+    InvalidValue(u16),
+    /// With redundant reads enabled, repeated reads of the register
+    /// disagreed
+    ReadMismatch(u16),
+    /// The configuration read back didn't match the expected CRC.  Holds
+    /// the CRC of the values read.
+    ConfigCrcMismatch(u16),
+    /// The connected part or silicon revision doesn't have the feature, see
+    /// [`revision`]
+    Unsupported,
 }
 
-/// Return the register address used to access this register
-fn reg_addr(reg: Registers) -> u8 {
-    ((reg as u16) & 0xff) as u8
+impl<E: fmt::Debug> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::I2c(e) => write!(f, "I2C error: {:?}", e),
+            Error::InvalidRegister(addr) => write!(f, "invalid register address {:#05x}", addr),
+            Error::VerifyFailed(addr) => write!(f, "verify failed for register {:#05x}", addr),
+            Error::NotInitialised => write!(f, "driver not initialised"),
+            Error::DataNotReady => write!(f, "gauge data not ready"),
+            Error::Timeout => write!(f, "timed out waiting for the gauge"),
+            Error::NvCopyFailed => write!(f, "nonvolatile memory copy failed"),
+            Error::InvalidArgument => write!(f, "invalid argument"),
+            Error::Inconsistent(addr) => {
+                write!(f, "register {:#05x} changed during a coherent read", addr)
+            }
+            Error::NvUpdatesExhausted => write!(f, "no nonvolatile memory updates left"),
+            Error::SuspectReading(addr) => {
+                write!(f, "implausible value in register {:#05x}", addr)
+            }
+            Error::InvalidValue(addr) => {
+                write!(f, "invalid value in register {:#05x}", addr)
+            }
+            Error::ReadMismatch(addr) => {
+                write!(f, "repeated reads of register {:#05x} disagreed", addr)
+            }
+            Error::ConfigCrcMismatch(crc) => {
+                write!(f, "configuration CRC mismatch, read back {:#06x}", crc)
+            }
+            Error::Unsupported => write!(f, "not supported by this part"),
+        }
+    }
 }
 
-#[allow(dead_code)]
-#[derive(Debug)]
+#[cfg(feature = "std")]
+impl<E: fmt::Debug> std::error::Error for Error<E> {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Represents the status of the MAX1720x fuel gauge IC read from the STATUS register
 pub struct Status {
     /// Power-On Reset
-    por: bool,
+    pub por: bool,
     /// Minimum current alert threshold exceeded
-    imn: bool,
-    /// Battery status
-    bst: bool,
+    pub imn: bool,
+    /// Battery status: set when no battery is present
+    pub bst: bool,
     /// Maximum currentl alert threshold exceeded
-    imx: bool,
+    pub imx: bool,
     /// State of charge 1% change alert
-    dsoci: bool,
+    pub dsoci: bool,
     /// Minimum voltage alert threshold exceeded
-    vmn: bool,
+    pub vmn: bool,
     /// Minimum temperature alert threshold exceeded
-    tmn: bool,
+    pub tmn: bool,
     /// Minimum SOC alert threshold exceeded
-    smn: bool,
+    pub smn: bool,
     /// Battery insertion
-    bi: bool,
+    pub bi: bool,
     /// Maximum voltage alert threshold exceeded
-    vmx: bool,
+    pub vmx: bool,
     /// Maximum temperature alert threshold exceeded
-    tmx: bool,
+    pub tmx: bool,
     /// Maximum SOC alert threshold exceeded
-    smx: bool,
+    pub smx: bool,
     /// Battery removal
-    br: bool,
+    pub br: bool,
 }
 
-pub struct MAX1720x<I2C, E> {
-    phantom: PhantomData<I2C>,
-    phantom_e: PhantomData<E>,
+/// Represents the additional status read from the STATUS2 register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status2 {
+    /// AtRate calculations are ready
+    pub at_rate_ready: bool,
+    /// Dynamic power calculations are ready
+    pub dp_ready: bool,
+    /// Serial number is ready to be read
+    pub sn_ready: bool,
+    /// Full detected: the cell has reached the end of charge
+    pub full_det: bool,
+    /// The gauge is in hibernate mode
+    pub hib: bool,
 }
 
-impl<I2C, E> MAX1720x<I2C, E>
-where
-    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
-{
-    /// Make a new MAX17205 driver
-    pub fn new(_bus: &mut I2C) -> Self {
-        Self {
-            phantom: PhantomData,
-            phantom_e: PhantomData,
-        }
-    }
+/// Represents the fuel gauge status read from the FSTAT register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FStat {
+    /// Data not ready: the first measurements after reset are not complete
+    pub dnr: bool,
+    /// Long relaxation: the cell has been relaxed for 48 to 96 minutes
+    pub rel_dt2: bool,
+    /// Full qualified: the end of charge conditions have been met
+    pub fq: bool,
+    /// Empty detection: the cell voltage is below VEmpty
+    pub edet: bool,
+    /// Relaxed cell detection: the cell is relaxed for OCV readings
+    pub rel_dt: bool,
+}
 
-    /// Get the fuel gauge status
-    pub fn status(&mut self, bus: &mut I2C) -> Result<Status, E> {
-        let mut raw = [0u8; 2];
-        let dev_addr = device_addr(Registers::Status);
-        let reg_addr = reg_addr(Registers::Status);
-        bus.write_read(dev_addr, &[reg_addr], &mut raw)?;
-        let raw = ((raw[1] as u16) << 8) | (raw[0] as u16);
-        Ok(Status {
+impl Status {
+    /// Decode a raw STATUS register value
+    pub fn from_raw(raw: u16) -> Self {
+        Status {
             br: raw & (1 << 15) != 0,
             smx: raw & (1 << 14) != 0,
             tmx: raw & (1 << 13) != 0,
@@ -151,41 +353,1422 @@ where
             bst: raw & (1 << 3) != 0,
             imn: raw & (1 << 2) != 0,
             por: raw & (1 << 1) != 0,
+        }
+    }
+}
+
+impl From<[u8; 2]> for Status {
+    fn from(bytes: [u8; 2]) -> Self {
+        Self::from_raw(decode::word(bytes))
+    }
+}
+
+impl Status2 {
+    /// Decode a raw Status2 register value
+    pub fn from_raw(raw: u16) -> Self {
+        Status2 {
+            at_rate_ready: raw & (1 << 13) != 0,
+            dp_ready: raw & (1 << 12) != 0,
+            sn_ready: raw & (1 << 8) != 0,
+            full_det: raw & (1 << 5) != 0,
+            hib: raw & (1 << 1) != 0,
+        }
+    }
+}
+
+impl From<[u8; 2]> for Status2 {
+    fn from(bytes: [u8; 2]) -> Self {
+        Self::from_raw(decode::word(bytes))
+    }
+}
+
+impl FStat {
+    /// Decode a raw FStat register value
+    pub fn from_raw(raw: u16) -> Self {
+        FStat {
+            rel_dt: raw & (1 << 9) != 0,
+            edet: raw & (1 << 8) != 0,
+            fq: raw & (1 << 7) != 0,
+            rel_dt2: raw & (1 << 6) != 0,
+            dnr: raw & (1 << 0) != 0,
+        }
+    }
+}
+
+impl From<[u8; 2]> for FStat {
+    fn from(bytes: [u8; 2]) -> Self {
+        Self::from_raw(decode::word(bytes))
+    }
+}
+
+/// A set of the main measurements, read with [`MAX1720x::measurements`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurements {
+    /// State of charge as a percentage
+    pub state_of_charge: f32,
+    /// Pack voltage in volts
+    pub voltage: f32,
+    /// Pack current in amps
+    pub current: f32,
+    /// Temperature in degrees Celsius
+    pub temperature: f32,
+}
+
+/// Round to the nearest integer, halves away from zero, as `f32::round`
+/// needs `std`
+fn round(value: f32) -> f32 {
+    // Every f32 of 2^23 or more is already a whole number
+    if value.is_nan() || value.abs() >= 8_388_608.0 {
+        return value;
+    }
+    let rounded = (value.abs() + 0.5) as u32 as f32;
+    if value < 0.0 {
+        -rounded
+    } else {
+        rounded
+    }
+}
+
+impl Measurements {
+    /// Size of the [`Measurements::encode`] wire format in bytes
+    pub const ENCODED_LEN: usize = 8;
+
+    /// Encode the measurements into a compact fixed-size format for
+    /// telemetry.  Each field is a little-endian integer:
+    ///
+    /// | Bytes | Field           | Type | LSB     |
+    /// |-------|-----------------|------|---------|
+    /// | 0 - 1 | state of charge | u16  | 1/256 % |
+    /// | 2 - 3 | voltage         | u16  | 1 mV    |
+    /// | 4 - 5 | current         | i16  | 1 mA    |
+    /// | 6 - 7 | temperature     | i16  | 1/256 °C |
+    ///
+    /// Values outside the range of a field saturate.
+    pub fn encode(&self) -> [u8; Self::ENCODED_LEN] {
+        // Float to integer casts saturate
+        let soc = round(self.state_of_charge * 256.0) as u16;
+        let voltage = round(self.voltage * 1000.0) as u16;
+        let current = round(self.current * 1000.0) as i16;
+        let temperature = round(self.temperature * 256.0) as i16;
+        let mut bytes = [0u8; Self::ENCODED_LEN];
+        bytes[0..2].copy_from_slice(&soc.to_le_bytes());
+        bytes[2..4].copy_from_slice(&voltage.to_le_bytes());
+        bytes[4..6].copy_from_slice(&current.to_le_bytes());
+        bytes[6..8].copy_from_slice(&temperature.to_le_bytes());
+        bytes
+    }
+
+    /// Decode measurements produced by [`Measurements::encode`]
+    pub fn decode(bytes: &[u8; Self::ENCODED_LEN]) -> Self {
+        Measurements {
+            state_of_charge: u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 256.0,
+            voltage: u16::from_le_bytes([bytes[2], bytes[3]]) as f32 / 1000.0,
+            current: i16::from_le_bytes([bytes[4], bytes[5]]) as f32 / 1000.0,
+            temperature: i16::from_le_bytes([bytes[6], bytes[7]]) as f32 / 256.0,
+        }
+    }
+}
+
+/// The cell model's temperature compensation parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemperatureCompensation {
+    /// Characteristic cell resistance at 25 °C (RComp0)
+    pub rcomp0: u16,
+    /// How the resistance changes with temperature (TempCo): the upper
+    /// byte above 25 °C and the lower byte below
+    pub temp_co: u16,
+}
+
+/// The gauge's remaining capacity estimates in mAh, read with
+/// [`MAX1720x::capacity_estimates`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CapacityEstimates {
+    /// Reported remaining capacity (RepCap)
+    pub reported: f32,
+    /// Mixed coulomb counter and voltage fuel gauge capacity (MixCap)
+    pub mixed: f32,
+    /// Voltage fuel gauge capacity (VFRemCap)
+    pub voltage_fuel_gauge: f32,
+    /// Capacity which can't be drawn at the present load (QResidual)
+    pub residual: f32,
+}
+
+/// How quickly the reported state of charge converges on the cell's real
+/// state near empty, from ConvgCfg.  The defaults suit most cells; cells
+/// with a high internal resistance may need gentler convergence to avoid
+/// jumps in the state of charge after long rests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceConfig {
+    /// State of charge as a percentage below which the reported capacity
+    /// converges towards empty, 0 - 30% in 2% steps (RepLow)
+    pub rep_low: f32,
+    /// Offset in volts added to the empty voltage to start converging,
+    /// 0 - 0.62 V in 20 mV steps (VoltLowOff)
+    pub volt_low_off: f32,
+    /// Minimum slope of the convergence, 0 - 15 (MinSlopeX)
+    pub min_slope_x: u8,
+    /// Maximum change in reported capacity per convergence stage, 0 - 7
+    /// (RepL_per_stg)
+    pub rep_l_per_stage: u8,
+}
+
+impl ConvergenceConfig {
+    /// Decode a raw ConvgCfg or nConvgCfg value
+    pub fn from_raw(raw: u16) -> Self {
+        ConvergenceConfig {
+            rep_low: (raw >> 12) as f32 * 2.0,
+            volt_low_off: ((raw >> 7) & 0x1F) as f32 * 0.02,
+            min_slope_x: ((raw >> 3) & 0xF) as u8,
+            rep_l_per_stage: (raw & 0x7) as u8,
+        }
+    }
+
+    /// Encode as a raw ConvgCfg value, or `None` if a field is out of range
+    pub fn to_raw(&self) -> Option<u16> {
+        let rep_low = round(self.rep_low / 2.0);
+        let volt_low_off = round(self.volt_low_off / 0.02);
+        if !(0.0..16.0).contains(&rep_low)
+            || !(0.0..32.0).contains(&volt_low_off)
+            || self.min_slope_x > 0xF
+            || self.rep_l_per_stage > 0x7
+        {
+            return None;
+        }
+        Some(
+            ((rep_low as u16) << 12)
+                | ((volt_low_off as u16) << 7)
+                | ((self.min_slope_x as u16) << 3)
+                | self.rep_l_per_stage as u16,
+        )
+    }
+}
+
+/// How register accesses are split into I2C transactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransactionStyle {
+    /// Read registers with a write and a read joined by a repeated start, and
+    /// write consecutive registers in one block transaction
+    #[default]
+    RepeatedStart,
+    /// Read registers with a write followed by a separate read transaction,
+    /// and write each register in its own transaction.  For I2C masters
+    /// which handle repeated starts poorly, such as bit-banged buses.
+    Separate,
+}
+
+/// Type-level cell count, used to restrict the values of the `CELLS`
+/// parameter of [`MAX1720x`]
+pub struct Cells<const N: usize>;
+
+/// Implemented for the cell counts the driver supports: 1 to 4 cells, which
+/// is as many as the device measures individually
+pub trait SupportedCells {}
+
+/// Implemented for the multi-cell pack configurations.  APIs which only make
+/// sense for a multi-cell pack require this.
+pub trait MultiCell: SupportedCells {}
+
+impl SupportedCells for Cells<1> {}
+impl SupportedCells for Cells<2> {}
+impl SupportedCells for Cells<3> {}
+impl SupportedCells for Cells<4> {}
+impl MultiCell for Cells<2> {}
+impl MultiCell for Cells<3> {}
+impl MultiCell for Cells<4> {}
+
+/// Cell voltage registers, in cell order
+const CELL_REGISTERS: [Register; 4] = [
+    Register::Cell1,
+    Register::Cell2,
+    Register::Cell3,
+    Register::Cell4,
+];
+
+/// A MAX1720x fuel gauge monitoring a pack of `CELLS` cells in series.  `V`
+/// is one of the part markers from [`variant`].
+pub struct MAX1720x<I2C, E, const CELLS: usize = 1, V = Generic> {
+    phantom: PhantomData<I2C>,
+    phantom_e: PhantomData<E>,
+    variant: PhantomData<V>,
+    initialised: bool,
+    rsense_milliohms: f32,
+    transaction_style: TransactionStyle,
+    pending: Option<nonblocking::Pending>,
+    validate: bool,
+    redundant: bool,
+    #[cfg(feature = "trace")]
+    trace: Option<trace::TraceHook>,
+    revision: Option<revision::DeviceRevision>,
+    addr_lower: u8,
+    addr_upper: u8,
+}
+
+impl<I2C, E> MAX1720x<I2C, E>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Make a new MAX17205 driver for a single cell pack
+    pub fn new(bus: &mut I2C) -> Self {
+        Self::with_cells(bus)
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Make a new driver for a pack of `CELLS` cells monitored by part `V`,
+    /// for example `MAX1720x::<_, _, 3, Max17205>::with_cells(&mut i2c)` for
+    /// a 3S pack.  Fails to compile if the part can't measure that many
+    /// cells.
+    pub fn with_cells(_bus: &mut I2C) -> Self
+    where
+        Cells<CELLS>: SupportedCells,
+        V: Variant,
+    {
+        const {
+            assert!(
+                CELLS <= V::MAX_CELLS,
+                "too many cells for this MAX1720x variant"
+            )
+        };
+        Self {
+            phantom: PhantomData,
+            phantom_e: PhantomData,
+            variant: PhantomData,
+            initialised: false,
+            rsense_milliohms: DEFAULT_RSENSE_MILLIOHMS,
+            transaction_style: TransactionStyle::RepeatedStart,
+            pending: None,
+            validate: false,
+            redundant: false,
+            #[cfg(feature = "trace")]
+            trace: None,
+            revision: None,
+            addr_lower: ADDR_LOWER,
+            addr_upper: ADDR_UPPER,
+        }
+    }
+
+    /// Set how register accesses are split into I2C transactions
+    pub fn set_transaction_style(&mut self, style: TransactionStyle) {
+        self.transaction_style = style;
+    }
+
+    /// Set the 7-bit I2C addresses the host sees the gauge at: `lower` for
+    /// registers 0x000 - 0x0FF, normally 0x36, and `upper` for registers
+    /// 0x100 - 0x1FF, normally 0x0B.  Only needed when the addresses are
+    /// translated, for example by an LTC4316.
+    pub fn set_i2c_addresses(&mut self, lower: u8, upper: u8) {
+        self.addr_lower = lower;
+        self.addr_upper = upper;
+    }
+
+    /// The 7-bit I2C addresses in use, as `(lower, upper)`
+    pub fn i2c_addresses(&self) -> (u8, u8) {
+        (self.addr_lower, self.addr_upper)
+    }
+
+    /// Return the I2C device address used to communicate when accessing
+    /// this register address
+    fn device_addr(&self, addr: u16) -> u8 {
+        if addr >= 0x100 {
+            self.addr_upper
+        } else {
+            self.addr_lower
+        }
+    }
+
+    /// Enable or disable checking measurements for plausibility.
+    ///
+    /// Measurements holding a value the datasheet documents as marking
+    /// missing data, see [`decode::is_sentinel`], always return
+    /// [`Error::DataNotReady`] if the gauge is still working on its first
+    /// measurements.  When validation is enabled, measurements which can't
+    /// be real return [`Error::SuspectReading`] instead of being converted:
+    /// a cell voltage above 5 V or pack voltage above 5 V per cell, and a
+    /// temperature beyond ±127 °C.  These are typical of a gauge which has browned out
+    /// or lost its configuration.
+    pub fn set_validation(&mut self, enable: bool) {
+        self.validate = enable;
+    }
+
+    /// Enable or disable redundant reads of measurements.
+    ///
+    /// When enabled, each measurement register is read twice and the values
+    /// compared.  As the gauge may update the register between the reads, a
+    /// mismatch is read a third time, which must match the second; anything
+    /// else returns [`Error::ReadMismatch`].  Coherent reads of several
+    /// registers already compare two reads, and return
+    /// [`Error::Inconsistent`] if they never agree.  Together with
+    /// [`integrity`] this detects values corrupted on the bus.
+    pub fn set_redundant_reads(&mut self, enable: bool) {
+        self.redundant = enable;
+    }
+
+    /// The sense resistor value used to convert current and capacity
+    /// readings, in milliohms
+    pub fn rsense_milliohms(&self) -> f32 {
+        self.rsense_milliohms
+    }
+
+    /// Set the sense resistor value used to convert current and capacity
+    /// readings, in milliohms.  This only affects the driver; the value the
+    /// gauge itself uses is in nRSense.
+    pub fn set_rsense_milliohms(&mut self, rsense_milliohms: f32) {
+        self.rsense_milliohms = rsense_milliohms;
+    }
+
+    /// Restart the fuel gauge algorithm (Config2.POR_CMD) so that it picks
+    /// up configuration changes.  The volatile registers are reloaded from
+    /// the shadow RAM, so the driver needs [`MAX1720x::init`] again
+    /// afterwards; [`MAX1720x::init_blocking`] waits for the reset to finish.
+    pub fn reset_fuel_gauge(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        self.initialised = false;
+        // Config2.POR_CMD is bit 0
+        self.modify(bus, Register::Config2, 1 << 0, 1 << 0)
+    }
+
+    /// Initialise the driver once the gauge is ready after a reset.
+    ///
+    /// Returns [`Error::DataNotReady`] if the gauge hasn't completed its
+    /// first measurements yet (FStat.DNR is set), in which case try again
+    /// later.  Otherwise the POR flag is cleared, so that a later reset can
+    /// be detected, and the measurement methods become available.
+    pub fn init(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        if self.fstat(bus)?.dnr {
+            return Err(Error::DataNotReady);
+        }
+        // Status.POR is bit 1
+        self.modify(bus, Register::Status, 1 << 1, 0)?;
+        self.initialised = true;
+        Ok(())
+    }
+
+    /// Wait for the gauge to be ready after a reset and then initialise the
+    /// driver, as [`MAX1720x::init`].  Returns [`Error::Timeout`] if the
+    /// gauge isn't ready within a couple of seconds.
+    pub fn init_blocking<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+    ) -> Result<(), Error<E>> {
+        self.poll_until(bus, delay, DATA_READY_TIMEOUT_MS, |gauge, bus| {
+            gauge.is_ready(bus)
+        })?;
+        self.init(bus)
+    }
+
+    /// Wait for the gauge to be ready after a reset, as
+    /// [`MAX1720x::init_blocking`], but leave the POR flag set so that the
+    /// reset is still seen if anything after this fails
+    fn wait_until_ready<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+    ) -> Result<(), Error<E>> {
+        self.initialised = false;
+        self.poll_until(bus, delay, DATA_READY_TIMEOUT_MS, |gauge, bus| {
+            gauge.is_ready(bus)
+        })?;
+        self.initialised = true;
+        Ok(())
+    }
+
+    /// Wait for a fuel gauge reset to be processed, which reloads the
+    /// volatile registers from the shadow RAM
+    fn wait_for_reset<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+    ) -> Result<(), Error<E>> {
+        // Config2.POR_CMD is bit 0 and clears once the reset has been
+        // processed
+        self.poll_until(bus, delay, DATA_READY_TIMEOUT_MS, |gauge, bus| {
+            Ok(gauge.read(bus, Register::Config2)? & (1 << 0) == 0)
         })
     }
 
+    /// Whether the gauge has finished any fuel gauge reset and its first
+    /// measurements
+    fn is_ready(&mut self, bus: &mut I2C) -> Result<bool, Error<E>> {
+        // Config2.POR_CMD is bit 0 and clears once a fuel gauge reset has
+        // been processed
+        Ok(self.read(bus, Register::Config2)? & (1 << 0) == 0 && !self.fstat(bus)?.dnr)
+    }
+
+    /// Reset the whole device, as if it had been power cycled, and wait for
+    /// it to respond again.  The nonvolatile configuration is not reloaded
+    /// into the shadow RAM until a fuel gauge reset, so this is normally
+    /// followed by [`MAX1720x::reset_fuel_gauge`] and
+    /// [`MAX1720x::init_blocking`].
+    pub fn hardware_reset<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+    ) -> Result<(), Error<E>> {
+        self.initialised = false;
+        self.write_register(bus, Register::Command.address(), CMD_HARDWARE_RESET)?;
+        delay.delay_ms(T_POR_MS);
+        // The device doesn't acknowledge while it is resetting
+        self.poll_until(bus, delay, RESET_TIMEOUT_MS, |gauge, bus| {
+            gauge.read(bus, Register::Status).map(|_| true)
+        })
+    }
+
+    /// Whether [`MAX1720x::init`] has succeeded
+    pub fn is_initialised(&self) -> bool {
+        self.initialised
+    }
+
+    /// Read a raw 16-bit register.
+    ///
+    /// `addr` is the 9-bit register address from the datasheet memory map
+    /// (0x000 - 0x1FF).  The I2C device address is chosen automatically.
+    pub fn read_register(&mut self, bus: &mut I2C, addr: u16) -> Result<u16, Error<E>> {
+        if addr > MAX_REGISTER {
+            return Err(Error::InvalidRegister(addr));
+        }
+        let mut value = [0u16];
+        self.read_words(bus, addr, &mut value)?;
+        Ok(value[0])
+    }
+
+    /// Write a raw 16-bit register.
+    ///
+    /// `addr` is the 9-bit register address from the datasheet memory map
+    /// (0x000 - 0x1FF).  The I2C device address is chosen automatically.
+    /// No checking is done on the value written, so consult the datasheet
+    /// before writing configuration registers.
+    ///
+    /// Writes to the nonvolatile shadow RAM (0x180 - 0x1DF) are read back
+    /// to verify them, as the datasheet recommends, and retried a few times
+    /// before returning [`Error::VerifyFailed`].
+    pub fn write_register(&mut self, bus: &mut I2C, addr: u16, value: u16) -> Result<(), Error<E>> {
+        if addr > MAX_REGISTER {
+            return Err(Error::InvalidRegister(addr));
+        }
+        let [lo, hi] = value.to_le_bytes();
+        if !nv::is_shadow_ram(addr) {
+            #[cfg(feature = "trace")]
+            self.trace(trace::Access::Write, addr, value);
+            return bus
+                .write(self.device_addr(addr), &[reg_addr(addr), lo, hi])
+                .map_err(Error::I2c);
+        }
+        for _ in 0..CONFIG_WRITE_ATTEMPTS {
+            #[cfg(feature = "trace")]
+            self.trace(trace::Access::Write, addr, value);
+            bus.write(self.device_addr(addr), &[reg_addr(addr), lo, hi])
+                .map_err(Error::I2c)?;
+            if self.read_register(bus, addr)? == value {
+                return Ok(());
+            }
+        }
+        Err(Error::VerifyFailed(addr))
+    }
+
+    /// Read a range of registers into `buf`, for example to capture a full
+    /// register dump for diagnostics.  The register at `range.start` is
+    /// stored in `buf[0]` and so on.
+    ///
+    /// The range may cover any part of the memory map, including both of
+    /// [`registers::MODEL_GAUGE_REGISTERS`] and [`registers::NV_REGISTERS`]
+    /// in one call.  Registers are read in blocks using the device's address
+    /// auto-increment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf` is shorter than `range`.
+    pub fn dump_registers(
+        &mut self,
+        bus: &mut I2C,
+        range: Range<u16>,
+        buf: &mut [u16],
+    ) -> Result<(), Error<E>> {
+        if range.end > MAX_REGISTER + 1 {
+            return Err(Error::InvalidRegister(range.end - 1));
+        }
+        let buf = &mut buf[..range.len()];
+        let mut addr = range.start;
+        for chunk in buf.chunks_mut(BLOCK_WORDS) {
+            // Don't let a block run across the lower/upper device address
+            // boundary
+            let (chunk, rest) = if addr < 0x100 && addr + chunk.len() as u16 > 0x100 {
+                chunk.split_at_mut((0x100 - addr) as usize)
+            } else {
+                (chunk, &mut [][..])
+            };
+            self.read_words(bus, addr, chunk)?;
+            addr += chunk.len() as u16;
+            if !rest.is_empty() {
+                self.read_words(bus, addr, rest)?;
+                addr += rest.len() as u16;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read consecutive registers starting at `addr` in a single transaction.
+    /// All of the registers must share the same device address and `words`
+    /// must be at most `BLOCK_WORDS` long.
+    fn read_words(&mut self, bus: &mut I2C, addr: u16, words: &mut [u16]) -> Result<(), Error<E>> {
+        let mut raw = [0u8; BLOCK_WORDS * 2];
+        let raw = &mut raw[..words.len() * 2];
+        match self.transaction_style {
+            TransactionStyle::RepeatedStart => bus
+                .write_read(self.device_addr(addr), &[reg_addr(addr)], raw)
+                .map_err(Error::I2c)?,
+            TransactionStyle::Separate => {
+                bus.write(self.device_addr(addr), &[reg_addr(addr)])
+                    .map_err(Error::I2c)?;
+                bus.read(self.device_addr(addr), raw).map_err(Error::I2c)?;
+            }
+        }
+        for (word, bytes) in words.iter_mut().zip(raw.chunks(2)) {
+            *word = ((bytes[1] as u16) << 8) | (bytes[0] as u16);
+        }
+        #[cfg(feature = "trace")]
+        for (reg, word) in (addr..).zip(words.iter()) {
+            self.trace(trace::Access::Read, reg, *word);
+        }
+        Ok(())
+    }
+
+    /// Write consecutive registers starting at `addr` in a single
+    /// transaction.  All of the registers must share the same device address
+    /// and be block-writable, and `words` must be at most `BLOCK_WORDS` long.
+    fn write_words(&mut self, bus: &mut I2C, addr: u16, words: &[u16]) -> Result<(), Error<E>> {
+        if self.transaction_style == TransactionStyle::Separate {
+            for (reg, value) in (addr..).zip(words) {
+                self.write_register(bus, reg, *value)?;
+            }
+            return Ok(());
+        }
+        let mut raw = [0u8; 1 + BLOCK_WORDS * 2];
+        raw[0] = reg_addr(addr);
+        for (bytes, word) in raw[1..].chunks_mut(2).zip(words.iter()) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        #[cfg(feature = "trace")]
+        for (reg, word) in (addr..).zip(words.iter()) {
+            self.trace(trace::Access::Write, reg, *word);
+        }
+        bus.write(self.device_addr(addr), &raw[..1 + words.len() * 2])
+            .map_err(Error::I2c)
+    }
+
+    /// Poll `done` until it returns true, waiting `POLL_INTERVAL_MS` between
+    /// attempts, or return [`Error::Timeout`] after `timeout_ms`.  Bus errors
+    /// are treated as the device being busy, since it doesn't acknowledge
+    /// while copying to nonvolatile memory or resetting.
+    fn poll_until<D, F>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        timeout_ms: u32,
+        mut done: F,
+    ) -> Result<(), Error<E>>
+    where
+        D: DelayMs<u32>,
+        F: FnMut(&mut Self, &mut I2C) -> Result<bool, Error<E>>,
+    {
+        let mut waited = 0;
+        loop {
+            if let Ok(true) = done(self, bus) {
+                return Ok(());
+            }
+            if waited >= timeout_ms {
+                return Err(Error::Timeout);
+            }
+            delay.delay_ms(POLL_INTERVAL_MS);
+            waited += POLL_INTERVAL_MS;
+        }
+    }
+
+    /// Read-modify-write one of the known registers, replacing the bits in
+    /// `mask` with those from `value`
+    fn modify(
+        &mut self,
+        bus: &mut I2C,
+        reg: Register,
+        mask: u16,
+        value: u16,
+    ) -> Result<(), Error<E>> {
+        let raw = self.read(bus, reg)?;
+        let new = (raw & !mask) | (value & mask);
+        if new != raw {
+            self.write_register(bus, reg.address(), new)?;
+        }
+        Ok(())
+    }
+
+    /// Read one of the known registers
+    fn read(&mut self, bus: &mut I2C, reg: Register) -> Result<u16, Error<E>> {
+        self.read_register(bus, reg.address())
+    }
+
+    /// Read one of the known registers as a measurement, which is only valid
+    /// once the driver has been initialised
+    fn read_measurement(&mut self, bus: &mut I2C, reg: Register) -> Result<f32, Error<E>> {
+        if !self.initialised {
+            return Err(Error::NotInitialised);
+        }
+        let raw = self.read_checked(bus, reg)?;
+        self.check_sentinel(bus, reg, raw)?;
+        self.check_plausible(reg, raw)?;
+        Ok(decode::value(reg, raw, self.rsense_milliohms))
+    }
+
+    /// Check that a raw measurement isn't a marker for missing data,
+    /// returning [`Error::DataNotReady`] if the gauge hasn't finished its
+    /// first measurements and [`Error::InvalidValue`] otherwise
+    fn check_sentinel(&mut self, bus: &mut I2C, reg: Register, raw: u16) -> Result<(), Error<E>> {
+        if self.is_no_data(bus, reg, raw)? {
+            Err(Error::InvalidValue(reg.address()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check whether a raw measurement is a marker for missing data,
+    /// returning [`Error::DataNotReady`] if it is and the gauge hasn't
+    /// finished its first measurements
+    fn is_no_data(&mut self, bus: &mut I2C, reg: Register, raw: u16) -> Result<bool, Error<E>> {
+        if !decode::is_sentinel(reg, raw) {
+            Ok(false)
+        } else if self.fstat(bus)?.dnr {
+            Err(Error::DataNotReady)
+        } else {
+            Ok(true)
+        }
+    }
+
+    /// Read one of the known registers, repeating the read to check it if
+    /// redundant reads are enabled
+    fn read_checked(&mut self, bus: &mut I2C, reg: Register) -> Result<u16, Error<E>> {
+        let first = self.read(bus, reg)?;
+        if !self.redundant {
+            return Ok(first);
+        }
+        let second = self.read(bus, reg)?;
+        if second == first {
+            return Ok(first);
+        }
+        // The gauge may have updated the register between the reads, but
+        // won't update it again so soon
+        let third = self.read(bus, reg)?;
+        if third == second {
+            Ok(third)
+        } else {
+            Err(Error::ReadMismatch(reg.address()))
+        }
+    }
+
+    /// With validation enabled, check that a raw measurement could be real
+    fn check_plausible(&self, reg: Register, raw: u16) -> Result<(), Error<E>> {
+        if !self.validate || decode::is_plausible(reg, raw, CELLS) {
+            Ok(())
+        } else {
+            Err(Error::SuspectReading(reg.address()))
+        }
+    }
+
+    /// Read one of the time estimate registers, which hold 0xFFFF when the
+    /// estimate doesn't apply.  Returns [`Error::DataNotReady`] rather than
+    /// `None` if the gauge hasn't finished its first measurements.
+    fn read_time(&mut self, bus: &mut I2C, reg: Register) -> Result<Option<Duration>, Error<E>> {
+        if !self.initialised {
+            return Err(Error::NotInitialised);
+        }
+        let raw = self.read_checked(bus, reg)?;
+        if self.is_no_data(bus, reg, raw)? {
+            return Ok(None);
+        }
+        Ok(Some(Duration::from_secs_f32(decode::value(
+            reg,
+            raw,
+            self.rsense_milliohms,
+        ))))
+    }
+
+    /// Read one of the known registers and convert it into its units
+    fn read_converted(&mut self, bus: &mut I2C, reg: Register) -> Result<f32, Error<E>> {
+        let raw = self.read(bus, reg)?;
+        Ok(decode::value(reg, raw, self.rsense_milliohms))
+    }
+
+    /// Read a set of registers so that all of the values come from the same
+    /// gauge update.  The registers are read repeatedly until two reads in a
+    /// row match, returning [`Error::Inconsistent`] if they never do.  A
+    /// single register is read once, or checked if redundant reads are
+    /// enabled.
+    fn read_coherent<const N: usize>(
+        &mut self,
+        bus: &mut I2C,
+        regs: [Register; N],
+    ) -> Result<[u16; N], Error<E>> {
+        let mut previous = [0u16; N];
+        if N == 1 {
+            previous[0] = self.read_checked(bus, regs[0])?;
+            return Ok(previous);
+        }
+        for (value, reg) in previous.iter_mut().zip(regs) {
+            *value = self.read(bus, reg)?;
+        }
+        let mut changed = regs[0];
+        for _ in 0..COHERENT_READ_ATTEMPTS {
+            let mut values = [0u16; N];
+            for (value, reg) in values.iter_mut().zip(regs) {
+                *value = self.read(bus, reg)?;
+            }
+            match (0..N).find(|&i| values[i] != previous[i]) {
+                None => return Ok(values),
+                Some(i) => changed = regs[i],
+            }
+            previous = values;
+        }
+        Err(Error::Inconsistent(changed.address()))
+    }
+
+    /// Read a set of registers as raw measurements from the same gauge
+    /// update, which is only valid once the driver has been initialised
+    fn read_raw_measurements<const N: usize>(
+        &mut self,
+        bus: &mut I2C,
+        regs: [Register; N],
+    ) -> Result<[u16; N], Error<E>> {
+        if !self.initialised {
+            return Err(Error::NotInitialised);
+        }
+        let raw = self.read_coherent(bus, regs)?;
+        for (reg, raw) in regs.iter().zip(raw) {
+            self.check_sentinel(bus, *reg, raw)?;
+            self.check_plausible(*reg, raw)?;
+        }
+        Ok(raw)
+    }
+
+    /// Read a set of registers as measurements from the same gauge update
+    fn read_measurements<const N: usize>(
+        &mut self,
+        bus: &mut I2C,
+        regs: [Register; N],
+    ) -> Result<[f32; N], Error<E>> {
+        let raw = self.read_raw_measurements(bus, regs)?;
+        Ok(core::array::from_fn(|i| {
+            decode::value(regs[i], raw[i], self.rsense_milliohms)
+        }))
+    }
+
+    /// Get the fuel gauge status
+    pub fn status(&mut self, bus: &mut I2C) -> Result<Status, Error<E>> {
+        Ok(Status::from_raw(self.read(bus, Register::Status)?))
+    }
+
+    /// Check whether a battery is connected, from the Bst bit of the STATUS
+    /// register
+    pub fn is_battery_present(&mut self, bus: &mut I2C) -> Result<bool, Error<E>> {
+        Ok(!self.status(bus)?.bst)
+    }
+
+    /// Get the additional STATUS2 flags, including hibernate state and full
+    /// detection
+    pub fn status2(&mut self, bus: &mut I2C) -> Result<Status2, Error<E>> {
+        Ok(Status2::from_raw(self.read(bus, Register::Status2)?))
+    }
+
+    /// Get the fuel gauge FSTAT flags, which say whether the measurements
+    /// are ready and whether the cell is relaxed
+    pub fn fstat(&mut self, bus: &mut I2C) -> Result<FStat, Error<E>> {
+        Ok(FStat::from_raw(self.read(bus, Register::FStat)?))
+    }
+
+    /// Check whether the gauge has detected the end of charge, from
+    /// Status2.FullDet.  This is set once the charge current falls below
+    /// IChgTerm while the cell voltage is high enough, and cleared again when
+    /// the state of charge falls below FullSOCThr, so a charger can stop on
+    /// this rather than waiting for the state of charge to reach 100%.
+    pub fn is_charge_complete(&mut self, bus: &mut I2C) -> Result<bool, Error<E>> {
+        Ok(self.status2(bus)?.full_det)
+    }
+
+    /// Get the charge termination current in amps, from IChgTerm
+    pub fn charge_termination_current(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_converted(bus, Register::IChgTerm)
+    }
+
+    /// Set the charge termination current in amps, which should match the
+    /// charger's termination current.  This is volatile and is reloaded from
+    /// nIChgTerm on reset.
+    pub fn set_charge_termination_current(
+        &mut self,
+        bus: &mut I2C,
+        amps: f32,
+    ) -> Result<(), Error<E>> {
+        let raw = Unit::Current.to_raw(amps, self.rsense_milliohms);
+        self.write_register(bus, Register::IChgTerm.address(), raw)
+    }
+
+    /// Enable or disable the alert on every 1% change in state of charge
+    /// (Config2.dSOCen).  Enabling it also enables the ALRT pin output
+    /// (Config.Aen), so a host can sleep until the state of charge changes.
+    /// The alert shows up as the dSOCi bit in [`Status`] and must be cleared
+    /// by the host.
+    pub fn set_soc_change_alert(&mut self, bus: &mut I2C, enable: bool) -> Result<(), Error<E>> {
+        // Config2.dSOCen is bit 7
+        let dsocen = 1 << 7;
+        self.modify(
+            bus,
+            Register::Config2,
+            dsocen,
+            if enable { dsocen } else { 0 },
+        )?;
+        if enable {
+            self.set_alert_output(bus, true)?;
+        }
+        Ok(())
+    }
+
     /// Get the current estimated state of charge as a percentage
-    pub fn state_of_charge(&mut self, bus: &mut I2C) -> Result<f32, E> {
-        let mut raw = [0u8; 2];
-        let dev_addr = device_addr(Registers::RepSOC);
-        let reg_addr = reg_addr(Registers::RepSOC);
-        bus.write_read(dev_addr, &[reg_addr], &mut raw)?;
-        let raw = ((raw[1] as u16) << 8) | (raw[0] as u16);
-        // Conversion ratio from datasheet Table 1
-        Ok((raw as f32) / 256.0)
+    pub fn state_of_charge(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::RepSOC)
     }
 
-    /// Get the current pack voltage in volts
-    pub fn voltage(&mut self, bus: &mut I2C) -> Result<f32, E> {
-        let mut raw = [0u8; 2];
-        let dev_addr = device_addr(Registers::Batt);
-        let reg_addr = reg_addr(Registers::Batt);
-        bus.write_read(dev_addr, &[reg_addr], &mut raw)?;
-        let raw = ((raw[1] as u16) << 8) | (raw[0] as u16);
-        // Conversion ratio from datasheet "Batt Register" register info
-        Ok((raw as f32) * 0.001_25)
+    /// Get the voltage of the lowest cell in volts, from VCell.  For a
+    /// single cell pack this is the cell voltage.  Cell undervoltage
+    /// protection should use this rather than the pack voltage, which hides
+    /// one weak cell among several.
+    pub fn lowest_cell_voltage(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::VCell)
     }
 
     /// Get the current pack current in amps
-    pub fn current(&mut self, bus: &mut I2C) -> Result<f32, E> {
-        let mut raw = [0u8; 2];
-        let dev_addr = device_addr(Registers::Current);
-        let reg_addr = reg_addr(Registers::Current);
-        bus.write_read(dev_addr, &[reg_addr], &mut raw)?;
-        let raw = ((raw[1] as u16) << 8) | (raw[0] as u16);
-        // Convert from twos complement form into a real signed integer
-        let raw = raw as i16;
-        // Conversion ratio from datasheet Table 1
-        Ok((raw as f32) * 0.000_156_25)
+    pub fn current(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::Current)
+    }
+
+    /// Get the instantaneous pack power in watts, positive when charging,
+    /// from Power
+    pub fn power(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::Power)
+    }
+
+    /// Get the filtered pack power in watts, positive when charging, from
+    /// AvgPower
+    pub fn average_power(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::AvgPower)
+    }
+
+    /// Get the pack temperature in degrees Celsius, from whichever source
+    /// is configured in nPackCfg
+    pub fn temperature(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::Temp)
+    }
+
+    /// Get the estimated time until the pack is empty at the present
+    /// discharge rate, from TTE.  Returns `None` while the pack is not
+    /// discharging.
+    pub fn time_to_empty(&mut self, bus: &mut I2C) -> Result<Option<Duration>, Error<E>> {
+        self.read_time(bus, Register::TTE)
+    }
+
+    /// Get the estimated time until the pack is full at the present charge
+    /// rate, from TTF.  Returns `None` while the pack is not charging.
+    pub fn time_to_full(&mut self, bus: &mut I2C) -> Result<Option<Duration>, Error<E>> {
+        self.read_time(bus, Register::TTF)
+    }
+
+    /// Get the filtered cell voltage ripple in volts.  Rising ripple under
+    /// the same load points to a poor connection or an aged cell.
+    pub fn voltage_ripple(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::VRipple)
+    }
+
+    /// Get the voltage fuel gauge's estimate of the cell open circuit
+    /// voltage in volts, from VFOCV.  Comparing this with the measured cell
+    /// voltage shows how heavily the cell is loaded.
+    pub fn open_circuit_voltage(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::VFOCV)
+    }
+
+    /// Get the gauge's estimate of the cell internal resistance in ohms,
+    /// from RCell.  This rises as the cell ages.
+    pub fn internal_resistance(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::RCell)
+    }
+
+    /// Get the temperature compensation parameters the cell model is using,
+    /// from RComp0 and TempCo.  The gauge adjusts these as it learns.
+    pub fn temperature_compensation(
+        &mut self,
+        bus: &mut I2C,
+    ) -> Result<TemperatureCompensation, Error<E>> {
+        let [rcomp0, temp_co] = self.read_coherent(bus, [Register::RComp0, Register::TempCo])?;
+        Ok(TemperatureCompensation { rcomp0, temp_co })
+    }
+
+    /// Get the convergence configuration in use, from ConvgCfg
+    pub fn convergence_config(&mut self, bus: &mut I2C) -> Result<ConvergenceConfig, Error<E>> {
+        Ok(ConvergenceConfig::from_raw(
+            self.read(bus, Register::ConvgCfg)?,
+        ))
+    }
+
+    /// Get the auxiliary input reading as a percentage of the THRM bias
+    /// voltage.  The AIN1/AIN2 channels must be enabled in nPackCfg for this
+    /// to be updated.
+    pub fn aux_input(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::AIN)
+    }
+
+    /// Get the capacity the pack was designed for in mAh, from DesignCap
+    pub fn design_capacity(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_converted(bus, Register::DesignCap)
+    }
+
+    /// Get the learned full capacity of the pack in mAh under nominal
+    /// conditions, from FullCapNom
+    pub fn full_capacity_nominal(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::FullCapNom)
+    }
+
+    /// Get the full capacity in mAh under the present conditions, as used
+    /// for the reported state of charge, from FullCapRep
+    pub fn full_capacity(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::FullCapRep)
+    }
+
+    /// Get the state of health as a percentage: the learned nominal full
+    /// capacity relative to the design capacity.  This starts around 100%
+    /// and falls as the cells age.  Returns [`Error::InvalidValue`] if
+    /// DesignCap is not set.
+    pub fn state_of_health(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        let [full_cap_nom, design_cap] =
+            self.read_raw_measurements(bus, [Register::FullCapNom, Register::DesignCap])?;
+        if design_cap == 0 {
+            return Err(Error::InvalidValue(Register::DesignCap.address()));
+        }
+        Ok(decode::state_of_health(full_cap_nom, design_cap))
+    }
+
+    /// Get the gauge's own age estimate as a percentage, from the Age
+    /// register: FullCapRep relative to DesignCap.  Unlike
+    /// [`state_of_health`](Self::state_of_health) this follows the present
+    /// temperature and load.
+    pub fn age(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::Age)
+    }
+
+    /// Get the capacity in mAh which can't be drawn from the pack at the
+    /// present load and temperature, from QResidual
+    pub fn residual_capacity(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::QResidual)
+    }
+
+    /// Get the remaining capacity in mAh from mixing the coulomb counter and
+    /// voltage fuel gauge, before the empty compensation and filtering
+    /// applied to the reported capacity, from MixCap
+    pub fn mixed_capacity(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::MixCap)
+    }
+
+    /// Get the remaining capacity in mAh estimated by the voltage fuel gauge
+    /// alone, from VFRemCap
+    pub fn voltage_fuel_gauge_capacity(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::VFRemCap)
+    }
+
+    /// Get the gauge's internal capacity estimates, read coherently, for
+    /// diagnosing a misbehaving state of charge
+    pub fn capacity_estimates(&mut self, bus: &mut I2C) -> Result<CapacityEstimates, Error<E>> {
+        let [reported, mixed, voltage_fuel_gauge, residual] = self.read_measurements(
+            bus,
+            [
+                Register::RepCap,
+                Register::MixCap,
+                Register::VFRemCap,
+                Register::QResidual,
+            ],
+        )?;
+        Ok(CapacityEstimates {
+            reported,
+            mixed,
+            voltage_fuel_gauge,
+            residual,
+        })
+    }
+
+    /// Get the total time the gauge has been operating, from the Timer and
+    /// TimerH registers.  This is retained across resets if the history is
+    /// saved to nonvolatile memory, so an unexpectedly small value means the
+    /// gauge has lost its state.  Returns [`Error::Inconsistent`] if the
+    /// registers keep changing while they are read.
+    pub fn uptime(&mut self, bus: &mut I2C) -> Result<Duration, Error<E>> {
+        // Timer rolls over into TimerH, so both are read from the same update
+        let [timer, timer_h] = self.read_coherent(bus, [Register::Timer, Register::TimerH])?;
+        Ok(decode::uptime(timer, timer_h))
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: PackMeasurement,
+{
+    /// Get the current pack voltage in volts
+    #[deprecated(note = "use `pack_voltage`, or `lowest_cell_voltage` for protection")]
+    pub fn voltage(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.pack_voltage(bus)
+    }
+
+    /// Get the voltage across the whole pack in volts, from Batt
+    pub fn pack_voltage(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::Batt)
+    }
+
+    /// Get the state of charge, pack voltage, current and temperature
+    pub fn measurements(&mut self, bus: &mut I2C) -> Result<Measurements, Error<E>> {
+        Ok(Measurements {
+            state_of_charge: self.state_of_charge(bus)?,
+            voltage: self.pack_voltage(bus)?,
+            current: self.current(bus)?,
+            temperature: self.temperature(bus)?,
+        })
+    }
+
+    /// Get the voltage of each cell in volts, starting from the cell at the
+    /// bottom of the stack.  For a single cell pack this is VCell, otherwise
+    /// the Cell1 - Cell4 registers, which are read coherently.  Only the
+    /// MAX17205/MAX17215 measure the cells of a multi-cell pack
+    /// individually; other parts return [`Error::Unsupported`].
+    pub fn cell_voltages(&mut self, bus: &mut I2C) -> Result<[f32; CELLS], Error<E>> {
+        if CELLS == 1 {
+            let mut voltages = [0.0; CELLS];
+            voltages[0] = self.read_measurement(bus, Register::VCell)?;
+            return Ok(voltages);
+        }
+        self.require(bus, Feature::CellVoltages)?;
+        self.read_measurements(bus, core::array::from_fn(|i| CELL_REGISTERS[i]))
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: SmartBattery,
+{
+    /// Get the charging current in amps recommended by the gauge, from the
+    /// SBS ChargingCurrent register.  The recommendation follows the step
+    /// charging and JEITA temperature ranges configured in nonvolatile
+    /// memory, so a host-controlled charger can apply it directly.
+    ///
+    /// The SBS registers are only updated when SBS mode is enabled in
+    /// nNVCfg0.enSBS, which is normally the case on the MAX17211/MAX17215.
+    pub fn charging_current(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::ChargingCurrent)
+    }
+
+    /// Get the charging voltage in volts recommended by the gauge, from the
+    /// SBS ChargingVoltage register.  See
+    /// [`charging_current`](Self::charging_current).
+    pub fn charging_voltage(&mut self, bus: &mut I2C) -> Result<f32, Error<E>> {
+        self.read_measurement(bus, Register::ChargingVoltage)
+    }
+}
+
+/// The spread of cell voltages in a multi-cell pack, read with
+/// [`MAX1720x::cell_imbalance`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellImbalance {
+    /// Difference between the highest and lowest cell voltages in volts
+    pub spread: f32,
+    /// Index of the cell with the lowest voltage, counting from the bottom
+    /// of the stack
+    pub weakest_cell: usize,
+    /// Whether the spread is above the threshold that was checked against
+    pub exceeds_threshold: bool,
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    Cells<CELLS>: MultiCell,
+    V: PackMeasurement,
+{
+    /// Read the cell voltages and work out how far apart they are.
+    /// `threshold` is the spread in volts above which the pack counts as
+    /// imbalanced.  The cell voltages are read coherently, as in
+    /// [`cell_voltages`](Self::cell_voltages).
+    pub fn cell_imbalance(
+        &mut self,
+        bus: &mut I2C,
+        threshold: f32,
+    ) -> Result<CellImbalance, Error<E>> {
+        let voltages = self.cell_voltages(bus)?;
+        let mut weakest_cell = 0;
+        let mut highest = voltages[0];
+        for (i, &voltage) in voltages.iter().enumerate() {
+            if voltage < voltages[weakest_cell] {
+                weakest_cell = i;
+            }
+            if voltage > highest {
+                highest = voltage;
+            }
+        }
+        let spread = highest - voltages[weakest_cell];
+        Ok(CellImbalance {
+            spread,
+            weakest_cell,
+            exceeds_threshold: spread > threshold,
+        })
+    }
+}
+
+/// Cell balancing threshold, configured in nPackCfg.BALCFG.  Balancing
+/// starts when the difference between cell voltages exceeds the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalancingThreshold {
+    /// Balancing disabled
+    Disabled,
+    /// 2.5 mV
+    Mv2_5,
+    /// 5 mV
+    Mv5,
+    /// 10 mV
+    Mv10,
+    /// 20 mV
+    Mv20,
+    /// 40 mV
+    Mv40,
+    /// 80 mV
+    Mv80,
+    /// 160 mV
+    Mv160,
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    Cells<CELLS>: MultiCell,
+    V: Balancing,
+{
+    /// Set the cell balancing threshold.  The single-cell MAX17201/MAX17211
+    /// variants don't have this method; with [`Generic`] the part is checked
+    /// at runtime instead, returning [`Error::Unsupported`] if it is one of
+    /// them.
+    pub fn set_balancing(
+        &mut self,
+        bus: &mut I2C,
+        threshold: BalancingThreshold,
+    ) -> Result<(), Error<E>> {
+        self.require(bus, Feature::Balancing)?;
+        // nPackCfg.BALCFG is bits 5-7
+        self.modify(bus, Register::NPackCfg, 0x7 << 5, (threshold as u16) << 5)
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: NonVolatile,
+{
+    /// Set the temperature compensation parameters, for example tuned for
+    /// the cell chemistry.  They are written both to RComp0 and TempCo,
+    /// taking effect immediately, and to nRComp0 and nTempCo in the shadow
+    /// RAM, so that [`copy_nv`](Self::copy_nv) makes them permanent.
+    pub fn set_temperature_compensation(
+        &mut self,
+        bus: &mut I2C,
+        compensation: &TemperatureCompensation,
+    ) -> Result<(), Error<E>> {
+        let TemperatureCompensation { rcomp0, temp_co } = *compensation;
+        self.write_register(bus, Register::RComp0.address(), rcomp0)?;
+        self.write_register(bus, Register::TempCo.address(), temp_co)?;
+        self.write_register(bus, Register::NRComp0.address(), rcomp0)?;
+        self.write_register(bus, Register::NTempCo.address(), temp_co)
+    }
+
+    /// Set the convergence configuration.  It is written both to ConvgCfg,
+    /// taking effect immediately, and to nConvgCfg in the shadow RAM, so
+    /// that [`copy_nv`](Self::copy_nv) makes it permanent.  Returns
+    /// [`Error::InvalidArgument`] if a field is out of range.
+    pub fn set_convergence_config(
+        &mut self,
+        bus: &mut I2C,
+        config: &ConvergenceConfig,
+    ) -> Result<(), Error<E>> {
+        let raw = config.to_raw().ok_or(Error::InvalidArgument)?;
+        self.write_register(bus, Register::ConvgCfg.address(), raw)?;
+        self.write_register(bus, Register::NConvgCfg.address(), raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::RefCell;
+
+    use super::*;
+    use crate::mock::{MockError, MockMax1720x, Quantity};
+
+    /// A bus shared between the gauge and another device, counting the
+    /// transactions at each address
+    struct SharedBus {
+        gauge: MockMax1720x,
+        /// Added to addresses on the way to the gauge, as by an address
+        /// translator
+        offset: u8,
+        lower: usize,
+        upper: usize,
+        other: usize,
+    }
+
+    impl SharedBus {
+        fn new(offset: u8) -> RefCell<Self> {
+            RefCell::new(SharedBus {
+                gauge: MockMax1720x::new(),
+                offset,
+                lower: 0,
+                upper: 0,
+                other: 0,
+            })
+        }
+
+        /// Count a transaction and return the gauge address it is for, if
+        /// any
+        fn route(&mut self, address: u8) -> Option<u8> {
+            match address.wrapping_sub(self.offset) {
+                ADDR_LOWER => self.lower += 1,
+                ADDR_UPPER => self.upper += 1,
+                _ => {
+                    self.other += 1;
+                    return None;
+                }
+            }
+            Some(address.wrapping_sub(self.offset))
+        }
+    }
+
+    /// A handle on a [`SharedBus`], as handed out by a bus manager
+    struct Proxy<'a>(&'a RefCell<SharedBus>);
+
+    impl Write for Proxy<'_> {
+        type Error = MockError;
+
+        fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), MockError> {
+            let mut bus = self.0.borrow_mut();
+            match bus.route(address) {
+                Some(address) => bus.gauge.write(address, bytes),
+                None => Ok(()),
+            }
+        }
+    }
+
+    impl Read for Proxy<'_> {
+        type Error = MockError;
+
+        fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), MockError> {
+            let mut bus = self.0.borrow_mut();
+            match bus.route(address) {
+                Some(address) => bus.gauge.read(address, buffer),
+                None => {
+                    buffer.fill(0);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    impl WriteRead for Proxy<'_> {
+        type Error = MockError;
+
+        fn write_read(
+            &mut self,
+            address: u8,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), MockError> {
+            let mut bus = self.0.borrow_mut();
+            match bus.route(address) {
+                Some(address) => bus.gauge.write_read(address, bytes, buffer),
+                None => {
+                    buffer.fill(0);
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shared_bus() {
+        let shared = SharedBus::new(0);
+        let mut gauge_bus = Proxy(&shared);
+        let mut max17205 = MAX1720x::new(&mut gauge_bus);
+        max17205.init(&mut gauge_bus).unwrap();
+        shared.borrow_mut().gauge.set(Quantity::StateOfCharge, 80.0);
+
+        // Another device is accessed between the gauge accesses, and a fresh
+        // proxy can be used for each access
+        let mut other = Proxy(&shared);
+        other.write(0x50, &[0x00, 0x12]).unwrap();
+        assert_eq!(max17205.state_of_charge(&mut Proxy(&shared)), Ok(80.0));
+        other.write(0x50, &[0x00, 0x34]).unwrap();
+        max17205
+            .write_register(&mut gauge_bus, Register::NDesignCap.address(), 0x1770)
+            .unwrap();
+
+        let bus = shared.borrow();
+        assert_eq!(bus.gauge.register(Register::NDesignCap.address()), 0x1770);
+        assert!(bus.lower > 0);
+        assert!(bus.upper > 0);
+        assert_eq!(bus.other, 2);
+    }
+
+    #[test]
+    fn register_banks_use_both_addresses() {
+        let shared = SharedBus::new(0);
+        let mut bus = Proxy(&shared);
+        let mut max17205 = MAX1720x::new(&mut bus);
+        for addr in [0x0FF, 0x100] {
+            shared.borrow_mut().gauge.set_register(addr, addr);
+        }
+
+        let (lower, upper) = {
+            let bus = shared.borrow();
+            (bus.lower, bus.upper)
+        };
+        assert_eq!(max17205.read_register(&mut bus, 0x0FF), Ok(0x0FF));
+        assert_eq!(shared.borrow().lower, lower + 1);
+        assert_eq!(max17205.read_register(&mut bus, 0x100), Ok(0x100));
+        assert_eq!(shared.borrow().upper, upper + 1);
+
+        // A block read across the boundary is split between the addresses
+        let mut words = [0; 4];
+        max17205
+            .dump_registers(&mut bus, 0x0FE..0x102, &mut words)
+            .unwrap();
+        assert_eq!(words[1..3], [0x0FF, 0x100]);
+        assert_eq!(shared.borrow().lower, lower + 2);
+        assert_eq!(shared.borrow().upper, upper + 2);
+    }
+
+    #[test]
+    fn translated_addresses() {
+        // The gauge appears at 0x46 and 0x1B behind the translator
+        let shared = SharedBus::new(0x10);
+        let mut bus = Proxy(&shared);
+        let mut max17205 = MAX1720x::new(&mut bus);
+        max17205.set_i2c_addresses(0x46, 0x1B);
+        max17205.init(&mut bus).unwrap();
+        assert_eq!(max17205.state_of_charge(&mut bus), Ok(50.0));
+        max17205
+            .write_register(&mut bus, Register::NDesignCap.address(), 0x1770)
+            .unwrap();
+
+        let bus = shared.borrow();
+        assert_eq!(bus.gauge.register(Register::NDesignCap.address()), 0x1770);
+        assert!(bus.lower > 0);
+        assert!(bus.upper > 0);
+        assert_eq!(bus.other, 0);
     }
 }
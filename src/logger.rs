@@ -0,0 +1,242 @@
+//! Fixed-capacity log of periodic measurements.
+//!
+//! [`MeasurementLog`] keeps the last `N` [`Measurements`] in a ring buffer
+//! without allocating, each stamped with the time from the application's
+//! own clock:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use core::time::Duration;
+//! # use max1720x::logger::MeasurementLog;
+//! # let uptime = Duration::from_secs(60);
+//! let mut log = MeasurementLog::<64>::new();
+//!
+//! // Once a minute
+//! log.record(&mut max17205, &mut i2c, uptime)?;
+//!
+//! if let Some(summary) = log.summary() {
+//!     println!("current {} A to {} A", summary.current.min, summary.current.max);
+//! }
+//! for sample in log.iter() {
+//!     println!("{:?}: {}%", sample.at, sample.measurements.state_of_charge);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use core::time::Duration;
+
+use crate::clock::Clock;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::variant::PackMeasurement;
+use crate::{Error, MAX1720x, Measurements};
+
+/// One entry in a [`MeasurementLog`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// When the measurements were taken, as the time since an arbitrary
+    /// start such as a [`Clock`]'s
+    pub at: Duration,
+    /// The measurements
+    pub measurements: Measurements,
+}
+
+/// Minimum, maximum and mean of one quantity over a log
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    /// Smallest value
+    pub min: f32,
+    /// Largest value
+    pub max: f32,
+    /// Mean of the values
+    pub mean: f32,
+}
+
+impl Stats {
+    fn new(value: f32) -> Self {
+        Stats {
+            min: value,
+            max: value,
+            mean: value,
+        }
+    }
+
+    fn add(&mut self, value: f32) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        // Summed here and divided in MeasurementLog::summary
+        self.mean += value;
+    }
+}
+
+/// Statistics over the samples in a [`MeasurementLog`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogSummary {
+    /// Time of the oldest sample
+    pub first: Duration,
+    /// Time of the newest sample
+    pub last: Duration,
+    /// State of charge as a percentage
+    pub state_of_charge: Stats,
+    /// Pack voltage in volts
+    pub voltage: Stats,
+    /// Current in amps
+    pub current: Stats,
+    /// Temperature in degrees Celsius
+    pub temperature: Stats,
+}
+
+/// The last `N` measurements, oldest first
+#[derive(Debug, Clone)]
+pub struct MeasurementLog<const N: usize> {
+    samples: [Sample; N],
+    /// Index of the oldest sample
+    start: usize,
+    len: usize,
+}
+
+impl<const N: usize> Default for MeasurementLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> MeasurementLog<N> {
+    /// An empty log
+    pub const fn new() -> Self {
+        const EMPTY: Sample = Sample {
+            at: Duration::ZERO,
+            measurements: Measurements {
+                state_of_charge: 0.0,
+                voltage: 0.0,
+                current: 0.0,
+                temperature: 0.0,
+            },
+        };
+        MeasurementLog {
+            samples: [EMPTY; N],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Maximum number of samples kept
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Number of samples in the log
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the log is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove every sample
+    pub fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+    }
+
+    /// Add a sample, dropping the oldest one if the log is full
+    pub fn push(&mut self, at: Duration, measurements: Measurements) {
+        if N == 0 {
+            return;
+        }
+        let sample = Sample { at, measurements };
+        if self.len < N {
+            self.samples[(self.start + self.len) % N] = sample;
+            self.len += 1;
+        } else {
+            self.samples[self.start] = sample;
+            self.start = (self.start + 1) % N;
+        }
+    }
+
+    /// Read the measurements from a gauge and add them to the log, stamped
+    /// with `at`
+    pub fn record<I2C, E, const CELLS: usize, V>(
+        &mut self,
+        gauge: &mut MAX1720x<I2C, E, CELLS, V>,
+        bus: &mut I2C,
+        at: Duration,
+    ) -> Result<(), Error<E>>
+    where
+        I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+        V: PackMeasurement,
+    {
+        let measurements = gauge.measurements(bus)?;
+        self.push(at, measurements);
+        Ok(())
+    }
+
+    /// Read the measurements from a gauge and add them to the log, stamped
+    /// with the time from `clock`
+    pub fn record_now<I2C, E, const CELLS: usize, V, C>(
+        &mut self,
+        gauge: &mut MAX1720x<I2C, E, CELLS, V>,
+        bus: &mut I2C,
+        clock: &mut C,
+    ) -> Result<(), Error<E>>
+    where
+        I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+        V: PackMeasurement,
+        C: Clock,
+    {
+        let measurements = gauge.measurements_at(bus, clock)?;
+        self.push(measurements.at(), measurements.value);
+        Ok(())
+    }
+
+    /// The newest sample
+    pub fn latest(&self) -> Option<&Sample> {
+        self.iter().last()
+    }
+
+    /// Iterate over the samples, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Sample> + ExactSizeIterator + '_ {
+        (0..self.len).map(move |i| &self.samples[(self.start + i) % N])
+    }
+
+    /// Statistics over all of the samples, or `None` if the log is empty
+    pub fn summary(&self) -> Option<LogSummary> {
+        let mut samples = self.iter();
+        let first = samples.next()?;
+        let m = &first.measurements;
+        let mut summary = LogSummary {
+            first: first.at,
+            last: first.at,
+            state_of_charge: Stats::new(m.state_of_charge),
+            voltage: Stats::new(m.voltage),
+            current: Stats::new(m.current),
+            temperature: Stats::new(m.temperature),
+        };
+        for sample in samples {
+            let m = &sample.measurements;
+            summary.last = sample.at;
+            summary.state_of_charge.add(m.state_of_charge);
+            summary.voltage.add(m.voltage);
+            summary.current.add(m.current);
+            summary.temperature.add(m.temperature);
+        }
+        let n = self.len as f32;
+        summary.state_of_charge.mean /= n;
+        summary.voltage.mean /= n;
+        summary.current.mean /= n;
+        summary.temperature.mean /= n;
+        Some(summary)
+    }
+}
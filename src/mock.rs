@@ -0,0 +1,520 @@
+//! Simulated MAX1720x for testing applications on the host.
+//!
+//! [`MockMax1720x`] models the device's register memory behind the
+//! embedded-hal I2C traits, so the real driver runs against it unchanged and
+//! application code gets exactly the same API as on hardware:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # use max1720x::alert::Alert;
+//! # use max1720x::mock::Quantity;
+//! let mut sim = MockMax1720x::new();
+//! sim.set_trajectory(Quantity::StateOfCharge, &[80.0, 79.0, 78.0]);
+//! sim.set(Quantity::Current, -1.5);
+//!
+//! let mut max17205 = MAX1720x::new(&mut sim);
+//! max17205.init(&mut sim)?;
+//! assert_eq!(max17205.state_of_charge(&mut sim)?, 80.0);
+//! assert_eq!(max17205.state_of_charge(&mut sim)?, 79.0);
+//!
+//! sim.raise_alert(Alert::VoltageLow);
+//! sim.fail_next(1);
+//! assert!(max17205.take_alerts(&mut sim).is_err());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only the register memory is modelled, not the fuel gauge algorithm.
+//! Commands complete instantly: a hardware reset restores the power-on
+//! register values and copying to nonvolatile memory counts down the
+//! remaining updates.
+
+use crate::alert::Alert;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::nv::NV_UPDATES;
+use crate::registers::Register;
+
+/// Maximum number of points in a [`Quantity`] trajectory
+pub const TRAJECTORY_LEN: usize = 32;
+
+/// Errors returned by the simulated bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MockError {
+    /// Nothing answered at the I2C address
+    NoAcknowledge(u8),
+    /// An error injected with [`MockMax1720x::fail_next`]
+    Injected,
+}
+
+/// A measured quantity which can be driven in the simulation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    /// State of charge in percent (RepSOC)
+    StateOfCharge,
+    /// Pack voltage in volts (Batt)
+    PackVoltage,
+    /// Voltage of every cell in volts (VCell, Cell1 - Cell4)
+    CellVoltage,
+    /// Current in amps (Current, AvgCurrent)
+    Current,
+    /// Temperature in degrees Celsius (Temp)
+    Temperature,
+}
+
+impl Quantity {
+    const ALL: [Quantity; 5] = [
+        Quantity::StateOfCharge,
+        Quantity::PackVoltage,
+        Quantity::CellVoltage,
+        Quantity::Current,
+        Quantity::Temperature,
+    ];
+
+    /// The register whose reads advance a trajectory of this quantity
+    fn register(self) -> Register {
+        match self {
+            Quantity::StateOfCharge => Register::RepSOC,
+            Quantity::PackVoltage => Register::Batt,
+            Quantity::CellVoltage => Register::VCell,
+            Quantity::Current => Register::Current,
+            Quantity::Temperature => Register::Temp,
+        }
+    }
+
+    /// Every register which holds this quantity
+    fn registers(self) -> &'static [Register] {
+        match self {
+            Quantity::StateOfCharge => &[Register::RepSOC],
+            Quantity::PackVoltage => &[Register::Batt],
+            Quantity::CellVoltage => &[
+                Register::VCell,
+                Register::Cell1,
+                Register::Cell2,
+                Register::Cell3,
+                Register::Cell4,
+            ],
+            Quantity::Current => &[Register::Current, Register::AvgCurrent],
+            Quantity::Temperature => &[Register::Temp],
+        }
+    }
+}
+
+/// A sequence of values stepped through one read at a time
+#[derive(Debug, Clone, Copy)]
+struct Trajectory {
+    points: [f32; TRAJECTORY_LEN],
+    len: usize,
+    next: usize,
+}
+
+/// A simulated MAX1720x on an I2C bus
+#[derive(Debug, Clone)]
+pub struct MockMax1720x {
+    registers: [u16; 0x200],
+    nv: [u16; 0x80],
+    nv_updates: u8,
+    nv_locked: bool,
+    pointer: u16,
+    trajectories: [Option<Trajectory>; 5],
+    fail_next: usize,
+}
+
+impl Default for MockMax1720x {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockMax1720x {
+    /// Make a simulated device which has just powered on with a half-charged
+    /// single cell at 25 °C and no load
+    pub fn new() -> Self {
+        let mut mock = MockMax1720x {
+            registers: [0; 0x200],
+            nv: [0; 0x80],
+            nv_updates: 0,
+            nv_locked: false,
+            pointer: 0,
+            trajectories: [None; 5],
+            fail_next: 0,
+        };
+        mock.set_register(Register::NPackCfg.address(), 0x0001);
+        // 10 mΩ sense resistor, LSB = 10 μΩ
+        mock.set_register(Register::NRSense.address(), 1000);
+        mock.nv.copy_from_slice(&mock.registers[0x180..]);
+        mock.power_on();
+        mock.set(Quantity::StateOfCharge, 50.0);
+        mock.set(Quantity::PackVoltage, 3.7);
+        mock.set(Quantity::CellVoltage, 3.7);
+        mock.set(Quantity::Temperature, 25.0);
+        mock
+    }
+
+    /// Read a register directly, without going through the bus
+    pub fn register(&self, addr: u16) -> u16 {
+        self.registers[addr as usize & 0x1FF]
+    }
+
+    /// Write a register directly, without going through the bus
+    pub fn set_register(&mut self, addr: u16, value: u16) {
+        self.registers[addr as usize & 0x1FF] = value;
+    }
+
+    /// Set a quantity to a fixed value, cancelling any trajectory
+    pub fn set(&mut self, quantity: Quantity, value: f32) {
+        self.trajectories[quantity as usize] = None;
+        self.store(quantity, value);
+    }
+
+    /// Step a quantity through `points`, moving on to the next point each
+    /// time the driver reads it and then holding the last point.  At most
+    /// [`TRAJECTORY_LEN`] points are used.
+    pub fn set_trajectory(&mut self, quantity: Quantity, points: &[f32]) {
+        let len = points.len().min(TRAJECTORY_LEN);
+        if len == 0 {
+            self.trajectories[quantity as usize] = None;
+            return;
+        }
+        let mut trajectory = Trajectory {
+            points: [0.0; TRAJECTORY_LEN],
+            len,
+            next: 0,
+        };
+        trajectory.points[..len].copy_from_slice(&points[..len]);
+        self.trajectories[quantity as usize] = Some(trajectory);
+    }
+
+    /// Set an alert bit in the STATUS register
+    pub fn raise_alert(&mut self, alert: Alert) {
+        self.registers[Register::Status.address() as usize] |= alert.bit();
+    }
+
+    /// Set or clear FStat.DNR, to simulate the gauge not having finished its
+    /// first measurements
+    pub fn set_data_ready(&mut self, ready: bool) {
+        let fstat = &mut self.registers[Register::FStat.address() as usize];
+        if ready {
+            *fstat &= !1;
+        } else {
+            *fstat |= 1;
+        }
+    }
+
+    /// Make the next `count` bus transactions fail with
+    /// [`MockError::Injected`]
+    pub fn fail_next(&mut self, count: usize) {
+        self.fail_next = count;
+    }
+
+    /// Number of times the nonvolatile memory has been written
+    pub fn nv_updates(&self) -> u8 {
+        self.nv_updates
+    }
+
+    /// Whether the nonvolatile memory has been locked
+    pub fn is_nv_locked(&self) -> bool {
+        self.nv_locked
+    }
+
+    /// Restore the power-on register values: the shadow RAM is reloaded
+    /// from nonvolatile memory and the POR flag is set
+    fn power_on(&mut self) {
+        self.registers[0x180..].copy_from_slice(&self.nv);
+        self.registers[Register::Status.address() as usize] = 1 << 1;
+        self.registers[Register::FStat.address() as usize] = 0;
+        self.registers[Register::Config2.address() as usize] = 0;
+        self.registers[Register::CommStat.address() as usize] = 0;
+    }
+
+    fn rsense_milliohms(&self) -> f32 {
+        self.register(Register::NRSense.address()) as f32 / 100.0
+    }
+
+    fn store(&mut self, quantity: Quantity, value: f32) {
+        let rsense = self.rsense_milliohms();
+        for reg in quantity.registers() {
+            self.set_register(reg.address(), reg.unit().to_raw(value, rsense));
+        }
+    }
+
+    /// Advance any trajectory driven by reads of `addr`
+    fn advance(&mut self, addr: u16) {
+        for quantity in Quantity::ALL {
+            if quantity.register().address() != addr {
+                continue;
+            }
+            if let Some(mut trajectory) = self.trajectories[quantity as usize] {
+                let value = trajectory.points[trajectory.next];
+                if trajectory.next + 1 < trajectory.len {
+                    trajectory.next += 1;
+                }
+                self.trajectories[quantity as usize] = Some(trajectory);
+                self.store(quantity, value);
+            }
+        }
+    }
+
+    /// Handle a write to the Command register
+    fn command(&mut self, command: u16) {
+        match command {
+            // Hardware reset
+            0x000F => self.power_on(),
+            // Copy NV block
+            0xE904 => {
+                if self.nv_updates < NV_UPDATES && !self.nv_locked {
+                    self.nv.copy_from_slice(&self.registers[0x180..]);
+                    self.nv_updates += 1;
+                } else {
+                    // CommStat.NVError
+                    self.registers[Register::CommStat.address() as usize] |= 1 << 2;
+                }
+            }
+            // Recall the nonvolatile memory into the shadow RAM
+            0xE001 => self.registers[0x180..0x1E0].copy_from_slice(&self.nv[..0x60]),
+            // Recall history pages: the simulation has no history
+            0xE226..=0xE2F0 => self.registers[0x1E0..0x1F0].fill(0xFFFF),
+            // Recall the history write and valid flags: nothing written
+            0xE2FB..=0xE2FE => self.registers[0x1E0..0x1F0].fill(0x0000),
+            // Recall the update count, one bit per update in both bytes
+            0xE2FA => {
+                let used = (1u16 << self.nv_updates) - 1;
+                self.registers[0x1ED] = (used << 8) | used;
+            }
+            // Lock the nonvolatile memory
+            0x6A00..=0x6AFF => self.nv_locked = true,
+            _ => {}
+        }
+    }
+
+    fn write_word(&mut self, addr: u16, value: u16) {
+        let addr = addr & 0x1FF;
+        if addr == Register::Command.address() {
+            self.command(value);
+        } else if addr == Register::Config2.address() {
+            // A fuel gauge reset (POR_CMD) completes instantly
+            self.set_register(addr, value & !1);
+        } else {
+            self.set_register(addr, value);
+        }
+    }
+
+    /// Check for injected errors and map the I2C address to the base of its
+    /// register bank
+    fn transaction(&mut self, address: u8) -> Result<u16, MockError> {
+        if self.fail_next > 0 {
+            self.fail_next -= 1;
+            return Err(MockError::Injected);
+        }
+        match address {
+            0x36 => Ok(0x000),
+            0x0B => Ok(0x100),
+            _ => Err(MockError::NoAcknowledge(address)),
+        }
+    }
+}
+
+impl Write for MockMax1720x {
+    type Error = MockError;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), MockError> {
+        let base = self.transaction(address)?;
+        let Some((&reg, data)) = bytes.split_first() else {
+            return Ok(());
+        };
+        self.pointer = base + reg as u16;
+        for chunk in data.chunks_exact(2) {
+            self.write_word(self.pointer, u16::from_le_bytes([chunk[0], chunk[1]]));
+            self.pointer += 1;
+        }
+        Ok(())
+    }
+}
+
+impl Read for MockMax1720x {
+    type Error = MockError;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), MockError> {
+        let base = self.transaction(address)?;
+        let start = base | (self.pointer & 0xFF);
+        for (addr, chunk) in (start..).zip(buffer.chunks_mut(2)) {
+            self.advance(addr);
+            let bytes = self.register(addr).to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
+impl WriteRead for MockMax1720x {
+    type Error = MockError;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), MockError> {
+        self.write(address, bytes)?;
+        // Only one transaction as far as injected errors are concerned
+        let fail_next = self.fail_next;
+        self.fail_next = 0;
+        let result = self.read(address, buffer);
+        self.fail_next = fail_next;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::*;
+    use crate::hal::blocking::delay::DelayMs;
+    use crate::{Error, MAX1720x};
+
+    struct NoDelay;
+
+    impl DelayMs<u32> for NoDelay {
+        fn delay_ms(&mut self, _ms: u32) {}
+    }
+
+    fn gauge(mock: &mut MockMax1720x) -> MAX1720x<MockMax1720x, MockError> {
+        let mut gauge = MAX1720x::new(mock);
+        gauge.init(mock).unwrap();
+        gauge
+    }
+
+    #[test]
+    fn register_reads_and_writes() {
+        let mut mock = MockMax1720x::new();
+        let mut gauge = gauge(&mut mock);
+
+        // Lower bank, through 0x36
+        mock.set_register(Register::RepCap.address(), 0x1234);
+        assert_eq!(
+            gauge.read_register(&mut mock, Register::RepCap.address()),
+            Ok(0x1234)
+        );
+        gauge
+            .write_register(&mut mock, Register::VAlrtTh.address(), 0xD296)
+            .unwrap();
+        assert_eq!(mock.register(Register::VAlrtTh.address()), 0xD296);
+
+        // Shadow RAM, through 0x0B and verified
+        gauge
+            .write_register(&mut mock, Register::NDesignCap.address(), 0x1770)
+            .unwrap();
+        assert_eq!(mock.register(Register::NDesignCap.address()), 0x1770);
+        assert_eq!(
+            gauge.read_register(&mut mock, Register::NDesignCap.address()),
+            Ok(0x1770)
+        );
+
+        assert_eq!(
+            gauge.read_register(&mut mock, 0x200),
+            Err(Error::InvalidRegister(0x200))
+        );
+    }
+
+    #[test]
+    fn measurements_round_trip() {
+        let mut mock = MockMax1720x::new();
+        let mut gauge = gauge(&mut mock);
+        mock.set(Quantity::StateOfCharge, 75.0);
+        mock.set(Quantity::Current, -0.5);
+        mock.set(Quantity::Temperature, -10.0);
+
+        assert_eq!(gauge.state_of_charge(&mut mock), Ok(75.0));
+        assert_eq!(gauge.current(&mut mock), Ok(-0.5));
+        assert_eq!(gauge.temperature(&mut mock), Ok(-10.0));
+
+        mock.set_trajectory(Quantity::StateOfCharge, &[60.0, 55.0]);
+        assert_eq!(gauge.state_of_charge(&mut mock), Ok(60.0));
+        assert_eq!(gauge.state_of_charge(&mut mock), Ok(55.0));
+        assert_eq!(gauge.state_of_charge(&mut mock), Ok(55.0));
+    }
+
+    #[test]
+    fn bus_errors() {
+        let mut mock = MockMax1720x::new();
+        let mut gauge = gauge(&mut mock);
+        mock.fail_next(1);
+        assert_eq!(
+            gauge.state_of_charge(&mut mock),
+            Err(Error::I2c(MockError::Injected))
+        );
+        assert!(gauge.state_of_charge(&mut mock).is_ok());
+
+        gauge.set_i2c_addresses(0x37, 0x0B);
+        assert_eq!(
+            gauge.state_of_charge(&mut mock),
+            Err(Error::I2c(MockError::NoAcknowledge(0x37)))
+        );
+    }
+
+    #[test]
+    fn nv_commit() {
+        let mut mock = MockMax1720x::new();
+        let mut gauge = gauge(&mut mock);
+        let mut delay = NoDelay;
+        let addr = Register::NDesignCap.address();
+
+        gauge.write_register(&mut mock, addr, 0x1770).unwrap();
+        gauge.copy_nv(&mut mock, &mut delay).unwrap();
+        assert_eq!(mock.nv_updates(), 1);
+        assert_eq!(
+            gauge.remaining_nv_updates(&mut mock, &mut delay),
+            Ok(NV_UPDATES - 1)
+        );
+
+        // The copy survives a reset, which reloads the shadow RAM
+        gauge.write_register(&mut mock, addr, 0x0BB8).unwrap();
+        gauge.hardware_reset(&mut mock, &mut delay).unwrap();
+        assert_eq!(mock.register(addr), 0x1770);
+
+        gauge.lock_nv(&mut mock, &mut delay).unwrap();
+        assert!(mock.is_nv_locked());
+        assert_eq!(
+            gauge.copy_nv(&mut mock, &mut delay),
+            Err(Error::NvCopyFailed)
+        );
+        assert_eq!(mock.nv_updates(), 1);
+    }
+
+    #[test]
+    fn no_data() {
+        let mut mock = MockMax1720x::new();
+        let mut gauge = MAX1720x::new(&mut mock);
+        assert_eq!(gauge.state_of_charge(&mut mock), Err(Error::NotInitialised));
+        mock.set_data_ready(false);
+        assert_eq!(gauge.init(&mut mock), Err(Error::DataNotReady));
+        mock.set_data_ready(true);
+        gauge.init(&mut mock).unwrap();
+
+        // 0xFFFF is the time estimates' documented marker for no estimate
+        mock.set_register(Register::TTE.address(), 0xFFFF);
+        assert_eq!(gauge.time_to_empty(&mut mock), Ok(None));
+        mock.set_data_ready(false);
+        assert_eq!(gauge.time_to_empty(&mut mock), Err(Error::DataNotReady));
+        mock.set_data_ready(true);
+        mock.set_register(Register::TTE.address(), 640);
+        assert_eq!(
+            gauge.time_to_empty(&mut mock),
+            Ok(Some(Duration::from_secs(3600)))
+        );
+
+        // Other registers have no marker, so a saturated current is real
+        mock.set_register(Register::Current.address(), i16::MAX as u16);
+        assert!(gauge.current(&mut mock).unwrap() > 5.0);
+        mock.set_register(Register::Current.address(), i16::MIN as u16);
+        assert!(gauge.current(&mut mock).unwrap() < -5.0);
+    }
+}
@@ -0,0 +1,168 @@
+//! Several gauges behind an I2C multiplexer.
+//!
+//! Every MAX1720x answers on the same I2C addresses, so more than one on a
+//! bus needs a multiplexer such as the TCA9548A.  [`MuxedGauges`] keeps a
+//! driver for each channel and switches the multiplexer before each access:
+//!
+//! ```no_run
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # use max1720x::mux::{MuxedGauges, Tca9548};
+//! let mut packs = MuxedGauges::<_, _, _, 4>::new(&mut i2c, Tca9548::new(0x70), [0, 1, 2, 3]);
+//! packs.init_all(&mut i2c)?;
+//! let summary = packs.summary(&mut i2c)?;
+//! println!("lowest pack at {}%", summary.lowest_state_of_charge);
+//! let soc = packs.with(&mut i2c, 2, |gauge, bus| gauge.state_of_charge(bus))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Other multiplexers can be supported by implementing [`Mux`].
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::variant::PackMeasurement;
+use crate::variant::{Generic, Variant};
+use crate::{Cells, Error, MAX1720x, Measurements, SupportedCells};
+
+/// An I2C multiplexer which connects one of its channels to the bus
+pub trait Mux<I2C, E> {
+    /// Connect `channel` to the bus
+    fn select(&mut self, bus: &mut I2C, channel: u8) -> Result<(), Error<E>>;
+}
+
+/// TCA9548A 8-channel I2C switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tca9548 {
+    address: u8,
+}
+
+impl Tca9548 {
+    /// A TCA9548A at the given 7-bit address, 0x70 - 0x77
+    pub fn new(address: u8) -> Self {
+        Tca9548 { address }
+    }
+}
+
+impl<I2C, E> Mux<I2C, E> for Tca9548
+where
+    I2C: Write<Error = E>,
+{
+    /// Enable only `channel`, 0 - 7.  Returns [`Error::InvalidArgument`]
+    /// for other channels.
+    fn select(&mut self, bus: &mut I2C, channel: u8) -> Result<(), Error<E>> {
+        if channel > 7 {
+            return Err(Error::InvalidArgument);
+        }
+        bus.write(self.address, &[1 << channel]).map_err(Error::I2c)
+    }
+}
+
+/// The combined state of all of the packs, from [`MuxedGauges::summary`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PacksSummary {
+    /// Lowest state of charge of any pack, as a percentage
+    pub lowest_state_of_charge: f32,
+    /// Lowest pack voltage in volts
+    pub lowest_voltage: f32,
+    /// Sum of the pack currents in amps
+    pub total_current: f32,
+    /// Highest pack temperature in degrees Celsius
+    pub highest_temperature: f32,
+}
+
+/// A driver for each of `N` gauges on the channels of a multiplexer
+pub struct MuxedGauges<M, I2C, E, const N: usize, const CELLS: usize = 1, V = Generic> {
+    mux: M,
+    channels: [u8; N],
+    gauges: [MAX1720x<I2C, E, CELLS, V>; N],
+}
+
+impl<M, I2C, E, const N: usize, const CELLS: usize, V> MuxedGauges<M, I2C, E, N, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    M: Mux<I2C, E>,
+{
+    /// Make a driver for the gauge on each of `channels`
+    pub fn new(bus: &mut I2C, mux: M, channels: [u8; N]) -> Self
+    where
+        Cells<CELLS>: SupportedCells,
+        V: Variant,
+    {
+        MuxedGauges {
+            mux,
+            channels,
+            gauges: core::array::from_fn(|_| MAX1720x::with_cells(bus)),
+        }
+    }
+
+    /// The multiplexer channel of each gauge
+    pub fn channels(&self) -> &[u8; N] {
+        &self.channels
+    }
+
+    /// Select the channel of gauge `index` and run `f` on its driver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than `N`.
+    pub fn with<T, F>(&mut self, bus: &mut I2C, index: usize, f: F) -> Result<T, Error<E>>
+    where
+        F: FnOnce(&mut MAX1720x<I2C, E, CELLS, V>, &mut I2C) -> Result<T, Error<E>>,
+    {
+        self.mux.select(bus, self.channels[index])?;
+        f(&mut self.gauges[index], bus)
+    }
+
+    /// Run `f` on every gauge in turn, collecting the results
+    pub fn for_each<T, F>(&mut self, bus: &mut I2C, mut f: F) -> [Result<T, Error<E>>; N]
+    where
+        F: FnMut(&mut MAX1720x<I2C, E, CELLS, V>, &mut I2C) -> Result<T, Error<E>>,
+    {
+        core::array::from_fn(|i| self.with(bus, i, &mut f))
+    }
+
+    /// Initialise every driver, stopping at the first error
+    pub fn init_all(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        for i in 0..N {
+            self.with(bus, i, |gauge, bus| gauge.init(bus))?;
+        }
+        Ok(())
+    }
+
+    /// Read the measurements of every pack
+    pub fn measurements(&mut self, bus: &mut I2C) -> [Result<Measurements, Error<E>>; N]
+    where
+        V: PackMeasurement,
+    {
+        self.for_each(bus, |gauge, bus| gauge.measurements(bus))
+    }
+
+    /// Combine the measurements of all of the packs, failing if any of them
+    /// can't be read
+    pub fn summary(&mut self, bus: &mut I2C) -> Result<PacksSummary, Error<E>>
+    where
+        V: PackMeasurement,
+    {
+        let mut summary = PacksSummary {
+            lowest_state_of_charge: f32::INFINITY,
+            lowest_voltage: f32::INFINITY,
+            total_current: 0.0,
+            highest_temperature: f32::NEG_INFINITY,
+        };
+        for i in 0..N {
+            let m = self.with(bus, i, |gauge, bus| gauge.measurements(bus))?;
+            summary.lowest_state_of_charge = summary.lowest_state_of_charge.min(m.state_of_charge);
+            summary.lowest_voltage = summary.lowest_voltage.min(m.voltage);
+            summary.total_current += m.current;
+            summary.highest_temperature = summary.highest_temperature.max(m.temperature);
+        }
+        Ok(summary)
+    }
+}
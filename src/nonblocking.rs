@@ -0,0 +1,125 @@
+//! Non-blocking long operations for superloops.
+//!
+//! Resets and copies to nonvolatile memory take from milliseconds to
+//! seconds.  Rather than waiting with a delay, they can be started and then
+//! driven to completion by calling [`MAX1720x::poll`] from the main loop,
+//! which returns [`nb::Error::WouldBlock`] until the operation is done:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::nb;
+//! # fn run_control_loop() {}
+//! max17205.start_copy_nv(&mut i2c)?;
+//! loop {
+//!     match max17205.poll(&mut i2c) {
+//!         Ok(()) => break,
+//!         Err(nb::Error::WouldBlock) => run_control_loop(),
+//!         Err(nb::Error::Other(e)) => return Err(e),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each call to `poll` does at most a few register reads.  There is no
+//! timeout, so the caller should give up if an operation doesn't complete
+//! in a reasonable time; the blocking equivalents wait a couple of seconds
+//! for a reset and about 7 s for a nonvolatile copy.
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::nv::CMD_COPY_NV_BLOCK;
+use crate::variant::NonVolatile;
+use crate::{Error, MAX1720x};
+
+/// An operation started by one of the `start_` methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Pending {
+    /// Waiting for the gauge to be ready, then initialising the driver
+    Init,
+    /// Waiting for a copy to nonvolatile memory
+    CopyNv,
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Start initialising the driver, as [`init_blocking`](Self::init_blocking)
+    /// does, for example after power on.  Call [`poll`](Self::poll) until
+    /// it completes.
+    pub fn start_init(&mut self) {
+        self.pending = Some(Pending::Init);
+    }
+
+    /// Start a fuel gauge reset so that the model is reloaded from the
+    /// shadow RAM, then initialise the driver once the gauge is ready.  Call
+    /// [`poll`](Self::poll) until it completes.
+    pub fn start_model_refresh(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        self.reset_fuel_gauge(bus)?;
+        self.pending = Some(Pending::Init);
+        Ok(())
+    }
+
+    /// Whether an operation is waiting to be completed by
+    /// [`poll`](Self::poll)
+    pub fn is_busy(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Make progress on the operation in progress.
+    ///
+    /// Returns `Ok(())` once it has completed, or straight away if nothing
+    /// is in progress, and [`nb::Error::WouldBlock`] while it is still
+    /// running.  Bus errors are treated as the device being busy, since it
+    /// doesn't acknowledge while resetting or copying.  After an error the
+    /// operation is abandoned.
+    pub fn poll(&mut self, bus: &mut I2C) -> nb::Result<(), Error<E>> {
+        let Some(pending) = self.pending else {
+            return Ok(());
+        };
+        let done = match pending {
+            Pending::Init => self.is_ready(bus),
+            Pending::CopyNv => self.is_nv_idle(bus),
+        };
+        match done {
+            Ok(true) => {}
+            Ok(false) | Err(Error::I2c(_)) => return Err(nb::Error::WouldBlock),
+            Err(e) => {
+                self.pending = None;
+                return Err(nb::Error::Other(e));
+            }
+        }
+
+        self.pending = None;
+        let result = match pending {
+            Pending::Init => self.init(bus),
+            Pending::CopyNv => self.check_nv_error(bus),
+        };
+        result.map_err(nb::Error::Other)
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: NonVolatile,
+{
+    /// Start copying the shadow RAM to nonvolatile memory, as
+    /// [`copy_nv`](Self::copy_nv) does.  Call [`poll`](Self::poll) until it
+    /// completes.
+    pub fn start_copy_nv(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        self.start_nv_command(bus, CMD_COPY_NV_BLOCK)?;
+        self.pending = Some(Pending::CopyNv);
+        Ok(())
+    }
+}
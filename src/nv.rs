@@ -0,0 +1,418 @@
+//! Nonvolatile configuration memory.
+//!
+//! The device configuration lives in nonvolatile memory and is copied into
+//! the shadow RAM at 0x180 - 0x1DF on reset.  The gauge always runs from the
+//! shadow RAM, so writing it changes the configuration immediately.
+
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::variant::NonVolatile;
+use crate::{Error, MAX1720x, BLOCK_WORDS};
+
+/// First register of the nonvolatile configuration
+pub(crate) const NV_START: u16 = 0x180;
+
+/// Command register value which copies the shadow RAM into nonvolatile
+/// memory
+pub(crate) const CMD_COPY_NV_BLOCK: u16 = 0xE904;
+
+/// Command register value which recalls the nonvolatile memory into the
+/// shadow RAM
+const CMD_RECALL_NV: u16 = 0xE001;
+
+/// Command register value which recalls the nonvolatile update count into
+/// register 0x1ED
+const CMD_RECALL_UPDATES: u16 = 0xE2FA;
+
+/// Register that the nonvolatile update count is recalled to
+const UPDATES_REGISTER: u16 = 0x1ED;
+
+/// Command register value which permanently locks every block of the
+/// nonvolatile memory
+const CMD_LOCK_NV: u16 = 0x6A1F;
+
+/// Time taken to recall from nonvolatile memory (tRECALL), in ms
+const T_RECALL_MS: u32 = 5;
+
+/// Number of times the nonvolatile memory can be written
+pub const NV_UPDATES: u8 = 7;
+
+/// Maximum time taken to copy the shadow RAM to nonvolatile memory (tBLOCK),
+/// in ms
+const T_BLOCK_MS: u32 = 7_360;
+
+/// CommStat.NVBusy is bit 1
+const COMMSTAT_NV_BUSY: u16 = 1 << 1;
+
+/// CommStat.NVError is bit 2
+const COMMSTAT_NV_ERROR: u16 = 1 << 2;
+
+/// Number of registers in the nonvolatile configuration
+pub const NV_IMAGE_WORDS: usize = 96;
+
+/// Size of a serialised [`NvImage`] in bytes
+pub const NV_IMAGE_LEN: usize = NV_IMAGE_WORDS * 2;
+
+/// A snapshot of the complete nonvolatile configuration, covering the shadow
+/// RAM registers 0x180 - 0x1DF.
+///
+/// Serialise it with [`NvImage::to_bytes`] to keep a backup for RMA analysis
+/// or to clone a configuration onto another pack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NvImage {
+    words: [u16; NV_IMAGE_WORDS],
+}
+
+impl NvImage {
+    /// Make an image from the raw register values, starting at 0x180
+    pub fn from_words(words: [u16; NV_IMAGE_WORDS]) -> Self {
+        Self { words }
+    }
+
+    /// The raw register values, starting at 0x180
+    pub fn words(&self) -> &[u16; NV_IMAGE_WORDS] {
+        &self.words
+    }
+
+    /// Get the value of a register in the image, or `None` if the register
+    /// isn't part of the nonvolatile configuration
+    pub fn get(&self, addr: u16) -> Option<u16> {
+        Self::index(addr).map(|i| self.words[i])
+    }
+
+    /// Set the value of a register in the image.  Returns `false` if the
+    /// register isn't part of the nonvolatile configuration.
+    pub fn set(&mut self, addr: u16, value: u16) -> bool {
+        match Self::index(addr) {
+            Some(i) => {
+                self.words[i] = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Serialise the image, with each register stored little-endian
+    pub fn to_bytes(&self) -> [u8; NV_IMAGE_LEN] {
+        let mut bytes = [0u8; NV_IMAGE_LEN];
+        for (chunk, word) in bytes.chunks_mut(2).zip(self.words.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Deserialise an image produced by [`NvImage::to_bytes`]
+    pub fn from_bytes(bytes: &[u8; NV_IMAGE_LEN]) -> Self {
+        let mut words = [0u16; NV_IMAGE_WORDS];
+        for (word, chunk) in words.iter_mut().zip(bytes.chunks(2)) {
+            *word = u16::from_le_bytes([chunk[0], chunk[1]]);
+        }
+        Self { words }
+    }
+
+    fn index(addr: u16) -> Option<usize> {
+        let i = addr.checked_sub(NV_START)? as usize;
+        if i < NV_IMAGE_WORDS {
+            Some(i)
+        } else {
+            None
+        }
+    }
+}
+
+/// The registers changed by [`MAX1720x::update_nv`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NvChanges(u128);
+
+impl NvChanges {
+    /// Whether nothing was changed, so no nonvolatile write was used
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Number of registers changed
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Whether the register at `addr` was changed
+    pub fn contains(self, addr: u16) -> bool {
+        NvImage::index(addr).is_some_and(|i| self.0 & (1 << i) != 0)
+    }
+
+    /// Iterate over the addresses of the changed registers
+    pub fn iter(self) -> impl Iterator<Item = u16> {
+        (0..NV_IMAGE_WORDS)
+            .filter(move |i| self.0 & (1 << i) != 0)
+            .map(|i| NV_START + i as u16)
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.0 |= 1 << i;
+    }
+}
+
+/// Number of nonvolatile words reserved for user data
+pub const USER_WORDS: usize = 7;
+
+/// The nonvolatile registers reserved for user data, in the order used by
+/// [`MAX1720x::user_memory`]
+pub const USER_REGISTERS: [Register; USER_WORDS] = [
+    Register::NUser18C,
+    Register::NUser18D,
+    Register::NUser1C4,
+    Register::NUser1C5,
+    Register::NUser1D0,
+    Register::NUser1D1,
+    Register::NUser1D4,
+];
+
+/// Whether a register is in the nonvolatile shadow RAM
+pub(crate) fn is_shadow_ram(addr: u16) -> bool {
+    NvImage::index(addr).is_some()
+}
+
+/// Whether a register in the nonvolatile configuration can be written.  The
+/// ROM ID is factory programmed.
+pub(crate) fn is_writable(addr: u16) -> bool {
+    !(Register::NRomID0.address()..=Register::NRomID3.address()).contains(&addr)
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: NonVolatile,
+{
+    /// Read the complete nonvolatile configuration from the shadow RAM
+    pub fn backup_config(&mut self, bus: &mut I2C) -> Result<NvImage, Error<E>> {
+        let mut words = [0u16; NV_IMAGE_WORDS];
+        let end = NV_START + NV_IMAGE_WORDS as u16;
+        self.dump_registers(bus, NV_START..end, &mut words)?;
+        Ok(NvImage { words })
+    }
+
+    /// Write a configuration backup into the shadow RAM and read it back to
+    /// verify it, rewriting any registers which don't match.  The ROM ID is
+    /// read-only and is skipped.
+    ///
+    /// This does not copy the configuration into nonvolatile memory, so it
+    /// will be lost on the next reset.
+    pub fn restore_config(&mut self, bus: &mut I2C, image: &NvImage) -> Result<(), Error<E>> {
+        let mut addr = NV_START;
+        for chunk in image.words.chunks(BLOCK_WORDS) {
+            let start = addr;
+            addr += chunk.len() as u16;
+            if (start..addr).all(is_writable) {
+                self.write_words(bus, start, chunk)?;
+            } else {
+                for (reg, value) in (start..addr).zip(chunk) {
+                    if is_writable(reg) {
+                        self.write_register(bus, reg, *value)?;
+                    }
+                }
+            }
+        }
+
+        let readback = self.backup_config(bus)?;
+        for (i, (want, got)) in image.words.iter().zip(readback.words.iter()).enumerate() {
+            let reg = NV_START + i as u16;
+            if is_writable(reg) && want != got {
+                // Verified and retried by write_register
+                self.write_register(bus, reg, *want)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Bring the nonvolatile memory in line with `image`, only using up a
+    /// nonvolatile write if something differs.  Firmware can call this on
+    /// every boot without wearing out the nonvolatile memory.
+    ///
+    /// The nonvolatile memory is first recalled into the shadow RAM, which
+    /// discards any unsaved changes there.  The registers which differ from
+    /// `image` are then written and verified, and copied to nonvolatile
+    /// memory with [`copy_nv`](Self::copy_nv).  The ROM ID is skipped.
+    /// Returns the registers which were changed.
+    pub fn update_nv<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        image: &NvImage,
+    ) -> Result<NvChanges, Error<E>> {
+        self.update_nv_with(bus, delay, |addr| image.get(addr))
+    }
+
+    /// Bring some of the nonvolatile registers in line with `values`, given
+    /// as `(address, value)` pairs, as [`update_nv`](Self::update_nv).
+    /// Registers outside the nonvolatile configuration return
+    /// [`Error::InvalidRegister`] before anything is written.
+    pub fn update_nv_registers<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        values: &[(u16, u16)],
+    ) -> Result<NvChanges, Error<E>> {
+        if let Some(&(addr, _)) = values.iter().find(|(addr, _)| !is_shadow_ram(*addr)) {
+            return Err(Error::InvalidRegister(addr));
+        }
+        self.update_nv_with(bus, delay, |addr| {
+            values
+                .iter()
+                .rev()
+                .find(|(reg, _)| *reg == addr)
+                .map(|(_, value)| *value)
+        })
+    }
+
+    /// Recall the nonvolatile memory, write the registers for which `want`
+    /// gives a different value, and copy to nonvolatile memory if any did
+    fn update_nv_with<D, F>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        want: F,
+    ) -> Result<NvChanges, Error<E>>
+    where
+        D: DelayMs<u32>,
+        F: Fn(u16) -> Option<u16>,
+    {
+        self.write_register(bus, Register::Command.address(), CMD_RECALL_NV)?;
+        delay.delay_ms(T_RECALL_MS);
+        let current = self.backup_config(bus)?;
+
+        let mut changes = NvChanges::default();
+        for (i, &have) in current.words.iter().enumerate() {
+            let addr = NV_START + i as u16;
+            match want(addr) {
+                Some(value) if value != have && is_writable(addr) => {
+                    self.write_register(bus, addr, value)?;
+                    changes.insert(i);
+                }
+                _ => {}
+            }
+        }
+        if !changes.is_empty() {
+            self.copy_nv(bus, delay)?;
+        }
+        Ok(changes)
+    }
+
+    /// Read the user data words from the shadow RAM, in the order of
+    /// [`USER_REGISTERS`]
+    pub fn user_memory(&mut self, bus: &mut I2C) -> Result<[u16; USER_WORDS], Error<E>> {
+        let mut words = [0u16; USER_WORDS];
+        for (word, reg) in words.iter_mut().zip(USER_REGISTERS) {
+            *word = self.read(bus, reg)?;
+        }
+        Ok(words)
+    }
+
+    /// Store user data words in nonvolatile memory, in the order of
+    /// [`USER_REGISTERS`].
+    ///
+    /// The words are written to the shadow RAM and verified, then the shadow
+    /// RAM is copied to nonvolatile memory with [`copy_nv`](Self::copy_nv).
+    /// The copy includes any other unsaved changes to the configuration.
+    /// Nothing is written if the words are already stored, to save the
+    /// limited nonvolatile writes.
+    pub fn set_user_memory<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        words: &[u16; USER_WORDS],
+    ) -> Result<(), Error<E>> {
+        if self.user_memory(bus)? == *words {
+            return Ok(());
+        }
+        self.write_verified(bus, &USER_REGISTERS, words)?;
+        self.copy_nv(bus, delay)
+    }
+
+    /// Write registers in the shadow RAM, each of which is verified by
+    /// [`write_register`](Self::write_register)
+    pub(crate) fn write_verified(
+        &mut self,
+        bus: &mut I2C,
+        regs: &[Register],
+        words: &[u16],
+    ) -> Result<(), Error<E>> {
+        for (reg, word) in regs.iter().zip(words) {
+            self.write_register(bus, reg.address(), *word)?;
+        }
+        Ok(())
+    }
+
+    /// Find out how many more times the nonvolatile memory can be written.
+    ///
+    /// Each copy to nonvolatile memory sets another bit in the update count,
+    /// which is stored twice, in the upper and lower bytes of 0x1ED.
+    pub fn remaining_nv_updates<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+    ) -> Result<u8, Error<E>> {
+        self.write_register(bus, Register::Command.address(), CMD_RECALL_UPDATES)?;
+        delay.delay_ms(T_RECALL_MS);
+        let [lo, hi] = self.read_register(bus, UPDATES_REGISTER)?.to_le_bytes();
+        let used = (lo | hi).count_ones() as u8;
+        Ok(NV_UPDATES.saturating_sub(used))
+    }
+
+    /// Permanently lock the nonvolatile memory so that it can never be
+    /// written again, waiting for the lock to complete.  This cannot be
+    /// undone.
+    pub fn lock_nv<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+    ) -> Result<(), Error<E>> {
+        self.start_nv_command(bus, CMD_LOCK_NV)?;
+        self.poll_until(bus, delay, T_BLOCK_MS, |gauge, bus| gauge.is_nv_idle(bus))?;
+        self.check_nv_error(bus)
+    }
+
+    /// Copy the shadow RAM into nonvolatile memory so that the configuration
+    /// survives a reset, waiting for the copy to finish.
+    ///
+    /// The nonvolatile memory can only be written a limited number of times
+    /// (7 on the MAX1720x), so only do this when the configuration has
+    /// actually changed.  The device doesn't acknowledge on the bus while
+    /// copying, which is retried until the copy completes or
+    /// [`Error::Timeout`] is returned.  Returns [`Error::NvCopyFailed`] if
+    /// the device reports the copy failed.
+    pub fn copy_nv<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+    ) -> Result<(), Error<E>> {
+        self.start_nv_command(bus, CMD_COPY_NV_BLOCK)?;
+        self.poll_until(bus, delay, T_BLOCK_MS, |gauge, bus| gauge.is_nv_idle(bus))?;
+        self.check_nv_error(bus)
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Start a command which writes the nonvolatile memory
+    pub(crate) fn start_nv_command(&mut self, bus: &mut I2C, command: u16) -> Result<(), Error<E>> {
+        self.write_register(bus, Register::CommStat.address(), 0)?;
+        self.write_register(bus, Register::Command.address(), command)
+    }
+
+    /// Whether the nonvolatile memory has finished the last command
+    pub(crate) fn is_nv_idle(&mut self, bus: &mut I2C) -> Result<bool, Error<E>> {
+        Ok(self.read(bus, Register::CommStat)? & COMMSTAT_NV_BUSY == 0)
+    }
+
+    /// Return [`Error::NvCopyFailed`] if the last nonvolatile memory command
+    /// failed
+    pub(crate) fn check_nv_error(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        if self.read(bus, Register::CommStat)? & COMMSTAT_NV_ERROR != 0 {
+            return Err(Error::NvCopyFailed);
+        }
+        Ok(())
+    }
+}
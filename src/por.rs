@@ -0,0 +1,209 @@
+//! Recovering from unexpected resets.
+//!
+//! A brown-out resets the gauge, reloading the shadow RAM from nonvolatile
+//! memory and the volatile registers from the shadow RAM, which silently
+//! undoes any configuration that wasn't saved.  The reset sets the POR bit
+//! in the STATUS register, which [`MAX1720x::init`] clears, so
+//! [`MAX1720x::check_por`] can be called periodically to notice a reset and
+//! put the configuration back:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::alert::AlertThresholds;
+//! # let thresholds = AlertThresholds::default();
+//! // Every few seconds
+//! max17205.check_por(&mut i2c, &mut delay, |gauge, i2c, _delay| {
+//!     gauge.set_alert_thresholds(i2c, &thresholds)
+//! })?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`MAX1720x::check_por_restore`] re-applies a configuration backup and
+//! learned parameters instead of a callback.
+
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::learned::LearnedParams;
+use crate::nv::NvImage;
+use crate::registers::Register;
+use crate::variant::NonVolatile;
+use crate::{Error, MAX1720x};
+
+/// Status.POR: set by a power-on or hardware reset
+const STATUS_POR: u16 = 1 << 1;
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Check whether the gauge has reset since it was initialised, and if
+    /// so restore it.  Returns whether a reset was found.
+    ///
+    /// After a reset this waits for the gauge's first measurements, calls
+    /// `restore` with the driver initialised, and then clears the POR bit.
+    /// If `restore` fails the POR bit is left set, so the next check tries
+    /// again.  `restore` must not call [`init`](Self::init) or
+    /// [`init_blocking`](Self::init_blocking), which clear the POR bit.
+    pub fn check_por<D, F>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        restore: F,
+    ) -> Result<bool, Error<E>>
+    where
+        D: DelayMs<u32>,
+        F: FnOnce(&mut Self, &mut I2C, &mut D) -> Result<(), Error<E>>,
+    {
+        if self.read(bus, Register::Status)? & STATUS_POR == 0 {
+            return Ok(false);
+        }
+        self.wait_until_ready(bus, delay)?;
+        restore(self, bus, delay)?;
+        self.modify(bus, Register::Status, STATUS_POR, 0)?;
+        Ok(true)
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: NonVolatile,
+{
+    /// As [`check_por`](Self::check_por), restoring a configuration backup
+    /// and learned parameters after a reset.
+    ///
+    /// `config` is written to the shadow RAM with
+    /// [`restore_config`](Self::restore_config) and the fuel gauge reset to
+    /// pick it up, then `learned` is restored with
+    /// [`restore_learned_params`](Self::restore_learned_params).  Either
+    /// may be left out.
+    pub fn check_por_restore<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        config: Option<&NvImage>,
+        learned: Option<&LearnedParams>,
+    ) -> Result<bool, Error<E>> {
+        self.check_por(bus, delay, |gauge, bus, delay| {
+            if let Some(image) = config {
+                gauge.restore_config(bus, image)?;
+                gauge.reset_fuel_gauge(bus)?;
+                // Not init_blocking, which would clear POR before the
+                // learned parameters are restored
+                gauge.wait_until_ready(bus, delay)?;
+            }
+            if let Some(params) = learned {
+                gauge.restore_learned_params(bus, params)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockError, MockMax1720x};
+    use crate::reg_addr;
+
+    struct NoDelay;
+
+    impl DelayMs<u32> for NoDelay {
+        fn delay_ms(&mut self, _ms: u32) {}
+    }
+
+    /// The mock with writes to one register failing
+    struct FailingBus {
+        mock: MockMax1720x,
+        fail_writes_to: Option<Register>,
+    }
+
+    impl Write for FailingBus {
+        type Error = MockError;
+
+        fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), MockError> {
+            if let Some(reg) = self.fail_writes_to {
+                if bytes.len() > 1 && bytes[0] == reg_addr(reg.address()) {
+                    return Err(MockError::Injected);
+                }
+            }
+            self.mock.write(address, bytes)
+        }
+    }
+
+    impl Read for FailingBus {
+        type Error = MockError;
+
+        fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), MockError> {
+            self.mock.read(address, buffer)
+        }
+    }
+
+    impl WriteRead for FailingBus {
+        type Error = MockError;
+
+        fn write_read(
+            &mut self,
+            address: u8,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), MockError> {
+            self.mock.write_read(address, bytes, buffer)
+        }
+    }
+
+    #[test]
+    fn failed_restore_is_retried() {
+        let mut bus = FailingBus {
+            mock: MockMax1720x::new(),
+            fail_writes_to: None,
+        };
+        let mut delay = NoDelay;
+        let mut gauge = MAX1720x::new(&mut bus);
+        gauge.init(&mut bus).unwrap();
+        assert_eq!(
+            gauge.check_por(&mut bus, &mut delay, |_, _, _| Ok(())),
+            Ok(false)
+        );
+
+        let status = Register::Status.address();
+        let rcomp0 = Register::RComp0.address();
+        bus.mock.set_register(rcomp0, 0x1234);
+        let config = gauge.backup_config(&mut bus).unwrap();
+        let learned = gauge.learned_params(&mut bus).unwrap();
+
+        // A brown-out loses the learned parameters and sets POR
+        bus.mock.set_register(rcomp0, 0);
+        bus.mock.set_register(status, STATUS_POR);
+        bus.fail_writes_to = Some(Register::RComp0);
+        assert_eq!(
+            gauge.check_por_restore(&mut bus, &mut delay, Some(&config), Some(&learned)),
+            Err(Error::I2c(MockError::Injected))
+        );
+        assert_eq!(bus.mock.register(status) & STATUS_POR, STATUS_POR);
+
+        bus.fail_writes_to = None;
+        assert_eq!(
+            gauge.check_por_restore(&mut bus, &mut delay, Some(&config), Some(&learned)),
+            Ok(true)
+        );
+        assert_eq!(bus.mock.register(rcomp0), 0x1234);
+        assert_eq!(bus.mock.register(status) & STATUS_POR, 0);
+        assert!(gauge.is_initialised());
+        assert_eq!(
+            gauge.check_por_restore(&mut bus, &mut delay, Some(&config), Some(&learned)),
+            Ok(false)
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Protector functions of the MAX1730x parts.
+//!
+//! The MAX17301 and MAX17311 pair the MAX1720x fuel gauge with battery
+//! protection: they drive the charge and discharge FETs and report why they
+//! have opened them.  Some registers move to make room for this, so these
+//! APIs are only available with a variant implementing
+//! [`Protector`]:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # use max1720x::variant::Max17301;
+//! # struct Charger;
+//! # impl Charger {
+//! #     fn stop(&mut self) {}
+//! #     fn set(&mut self, _amps: f32, _volts: f32) {}
+//! # }
+//! # let mut charger = Charger;
+//! let mut max17301 = MAX1720x::<_, _, 1, Max17301>::with_cells(&mut i2c);
+//! let status = max17301.protection_status(&mut i2c)?;
+//! if status.ovp {
+//!     charger.stop();
+//! }
+//! let limits = max17301.charge_limits(&mut i2c)?;
+//! charger.set(limits.current, limits.voltage);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The MAX1730x write-protect their configuration, so writes to the shadow
+//! RAM are ignored until [`MAX1720x::unlock_writes`] is called.
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::{Register, Unit};
+use crate::variant::Protector;
+use crate::{decode, Error, MAX1720x};
+
+/// Protection status register (ProtStatus)
+const PROT_STATUS: u16 = 0x0D9;
+
+/// Latched protection alerts (ProtAlrt)
+const PROT_ALRT: u16 = 0x0AF;
+
+/// Charge current recommended for the present temperature (ChargingCurrent)
+const CHARGING_CURRENT: u16 = 0x028;
+
+/// Charge voltage recommended for the present temperature (ChargingVoltage)
+const CHARGING_VOLTAGE: u16 = 0x02A;
+
+/// Written twice to CommStat to clear the write protection
+const WRITE_UNLOCK: u16 = 0x0000;
+
+/// Written twice to CommStat to set the write protection
+const WRITE_LOCK: u16 = 0x00F9;
+
+/// Protection faults, read from the ProtStatus or ProtAlrt register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtStatus {
+    /// Charge watchdog timer expired
+    pub chg_wdt: bool,
+    /// Too hot to charge
+    pub too_hot_c: bool,
+    /// Charge terminated because the cell is full
+    pub full: bool,
+    /// Too cold to charge
+    pub too_cold_c: bool,
+    /// Overvoltage protection
+    pub ovp: bool,
+    /// Overcurrent while charging
+    pub occp: bool,
+    /// Charge counter overflow protection
+    pub qovflw: bool,
+    /// Preparing to fail: a permanent failure is pending
+    pub prep_f: bool,
+    /// Cell imbalance
+    pub imbalance: bool,
+    /// Permanent failure: the pack is disabled
+    pub perm_fail: bool,
+    /// Die overtemperature
+    pub die_hot: bool,
+    /// Too hot to discharge
+    pub too_hot_d: bool,
+    /// Undervoltage protection
+    pub uvp: bool,
+    /// Overcurrent while discharging
+    pub odcp: bool,
+    /// Discharge fault detected by the resistor check
+    pub res_d_fault: bool,
+    /// Shutdown fault
+    pub shdn_fault: bool,
+}
+
+impl ProtStatus {
+    /// Decode a raw ProtStatus or ProtAlrt value
+    pub fn from_raw(raw: u16) -> Self {
+        ProtStatus {
+            chg_wdt: raw & (1 << 15) != 0,
+            too_hot_c: raw & (1 << 14) != 0,
+            full: raw & (1 << 13) != 0,
+            too_cold_c: raw & (1 << 12) != 0,
+            ovp: raw & (1 << 11) != 0,
+            occp: raw & (1 << 10) != 0,
+            qovflw: raw & (1 << 9) != 0,
+            prep_f: raw & (1 << 8) != 0,
+            imbalance: raw & (1 << 7) != 0,
+            perm_fail: raw & (1 << 6) != 0,
+            die_hot: raw & (1 << 5) != 0,
+            too_hot_d: raw & (1 << 4) != 0,
+            uvp: raw & (1 << 3) != 0,
+            odcp: raw & (1 << 2) != 0,
+            res_d_fault: raw & (1 << 1) != 0,
+            shdn_fault: raw & (1 << 0) != 0,
+        }
+    }
+}
+
+impl From<[u8; 2]> for ProtStatus {
+    fn from(bytes: [u8; 2]) -> Self {
+        Self::from_raw(decode::word(bytes))
+    }
+}
+
+/// Charging limits recommended by the protector
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChargeLimits {
+    /// Charge current in amps
+    pub current: f32,
+    /// Charge voltage per cell in volts
+    pub voltage: f32,
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: Protector,
+{
+    /// Get the protection faults which are active now
+    pub fn protection_status(&mut self, bus: &mut I2C) -> Result<ProtStatus, Error<E>> {
+        Ok(ProtStatus::from_raw(self.read_register(bus, PROT_STATUS)?))
+    }
+
+    /// Get the protection faults which have occurred since the alerts were
+    /// last cleared, and clear them
+    pub fn take_protection_alerts(&mut self, bus: &mut I2C) -> Result<ProtStatus, Error<E>> {
+        let raw = self.read_register(bus, PROT_ALRT)?;
+        if raw != 0 {
+            self.write_register(bus, PROT_ALRT, 0)?;
+        }
+        Ok(ProtStatus::from_raw(raw))
+    }
+
+    /// Get the charge current and voltage the protector permits at the
+    /// present temperature, following its JEITA configuration
+    pub fn charge_limits(&mut self, bus: &mut I2C) -> Result<ChargeLimits, Error<E>> {
+        let current = self.read_register(bus, CHARGING_CURRENT)?;
+        let voltage = self.read_register(bus, CHARGING_VOLTAGE)?;
+        Ok(ChargeLimits {
+            current: Unit::Current.convert(current, self.rsense_milliohms),
+            voltage: Unit::CellVoltage.convert(voltage, self.rsense_milliohms),
+        })
+    }
+
+    /// Clear the write protection so that the configuration can be changed.
+    /// CommStat has to be written twice in a row.
+    pub fn unlock_writes(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        self.write_comm_stat_twice(bus, WRITE_UNLOCK)
+    }
+
+    /// Set the write protection again after changing the configuration
+    pub fn lock_writes(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        self.write_comm_stat_twice(bus, WRITE_LOCK)
+    }
+
+    fn write_comm_stat_twice(&mut self, bus: &mut I2C, value: u16) -> Result<(), Error<E>> {
+        let addr = Register::CommStat.address();
+        self.write_register(bus, addr, value)?;
+        self.write_register(bus, addr, value)
+    }
+}
@@ -0,0 +1,157 @@
+//! Production provisioning of the nonvolatile configuration.
+//!
+//! [`MAX1720x::provision`] takes a complete configuration image, for example
+//! one captured from a characterised pack with
+//! [`MAX1720x::backup_config`], and stores it in nonvolatile memory:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # let image = max17205.backup_config(&mut i2c)?;
+//! match max17205.provision(&mut i2c, &mut delay, &image, false) {
+//!     Ok(report) => println!("{} updates left", report.remaining_updates),
+//!     Err(e) => println!("failed at {:?}: {}", e.step, e.error),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each step is checked before moving on to the next, and a failure reports
+//! the step it happened in, so a production line can tell a bad pack from a
+//! bad connection.
+
+use core::fmt;
+
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::nv::{is_writable, NvImage, NV_START};
+use crate::variant::NonVolatile;
+use crate::{Error, MAX1720x};
+
+/// A step of [`MAX1720x::provision`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvisionStep {
+    /// Checking that the nonvolatile memory can still be written
+    CheckUpdates,
+    /// Writing the image to the shadow RAM
+    Program,
+    /// Copying the shadow RAM to nonvolatile memory
+    Copy,
+    /// Resetting the device so that it reloads the nonvolatile memory
+    Reset,
+    /// Comparing the reloaded configuration with the image
+    Verify,
+    /// Locking the nonvolatile memory
+    Lock,
+}
+
+/// The step at which [`MAX1720x::provision`] failed, and why
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProvisionError<E> {
+    /// The step which failed
+    pub step: ProvisionStep,
+    /// The error from that step
+    pub error: Error<E>,
+}
+
+impl<E: fmt::Debug> fmt::Display for ProvisionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "provisioning failed at {:?}: {}", self.step, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug> std::error::Error for ProvisionError<E> {}
+
+/// The result of a successful [`MAX1720x::provision`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProvisionReport {
+    /// Nonvolatile updates left before provisioning
+    pub initial_updates: u8,
+    /// Nonvolatile updates left afterwards
+    pub remaining_updates: u8,
+    /// Whether the nonvolatile memory was locked
+    pub locked: bool,
+}
+
+/// Attach the step to an error
+trait AtStep<T, E> {
+    fn at(self, step: ProvisionStep) -> Result<T, ProvisionError<E>>;
+}
+
+impl<T, E> AtStep<T, E> for Result<T, Error<E>> {
+    fn at(self, step: ProvisionStep) -> Result<T, ProvisionError<E>> {
+        self.map_err(|error| ProvisionError { step, error })
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    V: NonVolatile,
+{
+    /// Store a configuration image in nonvolatile memory.
+    ///
+    /// In order, this:
+    /// 1. checks that there is a nonvolatile update left, returning
+    ///    [`Error::NvUpdatesExhausted`] otherwise
+    /// 2. writes the image to the shadow RAM with
+    ///    [`restore_config`](Self::restore_config)
+    /// 3. copies it to nonvolatile memory with [`copy_nv`](Self::copy_nv)
+    /// 4. resets the device and fuel gauge and waits for it to be ready
+    /// 5. reads the configuration back and compares it with the image,
+    ///    returning [`Error::VerifyFailed`] for the first mismatch
+    /// 6. if `lock` is set, permanently locks the nonvolatile memory with
+    ///    [`lock_nv`](Self::lock_nv)
+    ///
+    /// The driver is initialised afterwards.
+    pub fn provision<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        image: &NvImage,
+        lock: bool,
+    ) -> Result<ProvisionReport, ProvisionError<E>> {
+        let initial_updates = self
+            .remaining_nv_updates(bus, delay)
+            .at(ProvisionStep::CheckUpdates)?;
+        if initial_updates == 0 {
+            return Err(Error::NvUpdatesExhausted).at(ProvisionStep::CheckUpdates);
+        }
+
+        self.restore_config(bus, image).at(ProvisionStep::Program)?;
+        self.copy_nv(bus, delay).at(ProvisionStep::Copy)?;
+
+        self.hardware_reset(bus, delay).at(ProvisionStep::Reset)?;
+        self.reset_fuel_gauge(bus).at(ProvisionStep::Reset)?;
+        self.init_blocking(bus, delay).at(ProvisionStep::Reset)?;
+
+        let readback = self.backup_config(bus).at(ProvisionStep::Verify)?;
+        for (addr, (want, got)) in (NV_START..).zip(image.words().iter().zip(readback.words())) {
+            if is_writable(addr) && want != got {
+                return Err(Error::VerifyFailed(addr)).at(ProvisionStep::Verify);
+            }
+        }
+        let remaining_updates = self
+            .remaining_nv_updates(bus, delay)
+            .at(ProvisionStep::Verify)?;
+
+        if lock {
+            self.lock_nv(bus, delay).at(ProvisionStep::Lock)?;
+        }
+        Ok(ProvisionReport {
+            initial_updates,
+            remaining_updates,
+            locked: lock,
+        })
+    }
+}
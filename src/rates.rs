@@ -0,0 +1,94 @@
+//! Rates of change between two sets of measurements.
+//!
+//! [`Rates::between`] works out how fast the state of charge and voltage
+//! changed between two timestamped [`Measurements`], and the charge moved,
+//! with the units and signs fixed:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::rates::Rates;
+//! # let mut ticks = 0;
+//! # let mut clock = || {
+//! #     ticks += 60_000;
+//! #     ticks
+//! # };
+//! # fn flag_anomaly(_rates: &Rates) {}
+//! let before = max17205.measurements_at(&mut i2c, &mut clock)?;
+//! // ...
+//! let after = max17205.measurements_at(&mut i2c, &mut clock)?;
+//!
+//! if let Some(rates) = Rates::between(&before, &after) {
+//!     if rates.current > 0.0 && rates.soc_per_hour < 0.0 {
+//!         flag_anomaly(&rates);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Everything is positive when charging.  The current is only known at the
+//! two ends, so the charge moved assumes it changed linearly in between;
+//! over long intervals the gauge's coulomb counter in
+//! [`session`](crate::session) is more accurate.
+
+use core::time::Duration;
+
+use crate::clock::Timestamped;
+use crate::Measurements;
+
+/// Rates of change over an interval, from [`Rates::between`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rates {
+    /// Time between the two measurements
+    pub interval: Duration,
+    /// Change in state of charge in percent per hour
+    pub soc_per_hour: f32,
+    /// Change in pack voltage in volts per hour
+    pub voltage_per_hour: f32,
+    /// Change in temperature in degrees Celsius per hour
+    pub temperature_per_hour: f32,
+    /// Charge moved into the pack in mAh
+    pub charge: f32,
+    /// Average current in amps
+    pub current: f32,
+}
+
+impl Rates {
+    /// The rates between measurements taken `interval` apart.  Returns
+    /// `None` for a zero interval.
+    pub fn new(interval: Duration, earlier: &Measurements, later: &Measurements) -> Option<Self> {
+        let hours = interval.as_secs_f32() / 3600.0;
+        if hours <= 0.0 {
+            return None;
+        }
+        let current = (earlier.current + later.current) / 2.0;
+        Some(Rates {
+            interval,
+            soc_per_hour: (later.state_of_charge - earlier.state_of_charge) / hours,
+            voltage_per_hour: (later.voltage - earlier.voltage) / hours,
+            temperature_per_hour: (later.temperature - earlier.temperature) / hours,
+            charge: current * hours * 1000.0,
+            current,
+        })
+    }
+
+    /// The rates between two timestamped measurements.  Returns `None`
+    /// unless `later` was taken after `earlier`.
+    pub fn between(
+        earlier: &Timestamped<Measurements>,
+        later: &Timestamped<Measurements>,
+    ) -> Option<Self> {
+        let interval = later.at().checked_sub(earlier.at())?;
+        Self::new(interval, &earlier.value, &later.value)
+    }
+}
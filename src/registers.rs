@@ -0,0 +1,552 @@
+//! The MAX1720x register map.
+//!
+//! Every named register from the datasheet memory map is listed in
+//! [`Register`] along with the [`Unit`] of its contents.  Together with
+//! [`MAX1720x::read_register`](crate::MAX1720x::read_register) and
+//! [`MAX1720x::write_register`](crate::MAX1720x::write_register) this gives
+//! access to registers which don't yet have a typed getter:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::registers::{Register, DEFAULT_RSENSE_MILLIOHMS};
+//! let raw = max17205.read_register(&mut i2c, Register::AvgCurrent.address())?;
+//! let amps = Register::AvgCurrent.unit().convert(raw, DEFAULT_RSENSE_MILLIOHMS);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Registers 0x000 - 0x0FF are the ModelGauge m5 registers, 0x180 - 0x1DF are
+//! the shadow RAM copies of the nonvolatile configuration (prefixed with `N`).
+//! Of the SBS registers at 0x100 - 0x17F only ChargingCurrent and
+//! ChargingVoltage are listed; the others can be reached by address.
+
+use core::ops::Range;
+
+/// The ModelGauge m5 register bank, accessed through the lower device address
+pub const MODEL_GAUGE_REGISTERS: Range<u16> = 0x000..0x100;
+
+/// The nonvolatile memory shadow RAM bank, accessed through the upper device
+/// address
+pub const NV_REGISTERS: Range<u16> = 0x180..0x200;
+
+/// Sense resistor value assumed by the conversions, in milliohms.  This is the
+/// value used on the Maxim evaluation kits.
+pub const DEFAULT_RSENSE_MILLIOHMS: f32 = 10.0;
+
+/// The units of a register's contents, used to convert raw register values
+/// into engineering units.
+///
+/// Conversion ratios are from datasheet Table 1 "ModelGauge m5 Register
+/// Standard Resolutions" unless noted otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// Bitfield or packed value with no direct conversion
+    Raw,
+    /// Capacity in mAh, LSB = 5.0 μVh / Rsense
+    Capacity,
+    /// Percentage, LSB = 1/256 %
+    Percentage,
+    /// Cell voltage in volts, LSB = 78.125 μV
+    CellVoltage,
+    /// Pack voltage in volts, LSB = 1.25 mV
+    PackVoltage,
+    /// Ripple voltage in volts, LSB = 1.25 mV / 128
+    RippleVoltage,
+    /// Signed current in amps, LSB = 1.5625 μV / Rsense
+    Current,
+    /// Ratiometric measurement as a percentage of the THRM bias voltage,
+    /// LSB = 100/65536 %
+    Ratio,
+    /// Signed temperature in degrees Celsius, LSB = 1/256 °C
+    Temperature,
+    /// Resistance in ohms, LSB = 1/4096 Ω
+    Resistance,
+    /// Time in seconds, LSB = 5.625 s
+    Time,
+    /// Signed power in watts, LSB = 8 μV² / Rsense
+    Power,
+    /// Charge cycles, LSB = 16 %
+    Cycles,
+}
+
+impl Unit {
+    /// Size of one LSB of a register in this unit, for the given sense
+    /// resistor value in milliohms.  Returns 1.0 for [`Unit::Raw`].
+    pub fn lsb(self, rsense_milliohms: f32) -> f32 {
+        match self {
+            Unit::Raw => 1.0,
+            Unit::Capacity => 5.0 / rsense_milliohms,
+            Unit::Percentage => 1.0 / 256.0,
+            Unit::CellVoltage => 0.000_078_125,
+            Unit::PackVoltage => 0.001_25,
+            Unit::RippleVoltage => 0.001_25 / 128.0,
+            Unit::Current => 0.001_562_5 / rsense_milliohms,
+            Unit::Ratio => 100.0 / 65536.0,
+            Unit::Temperature => 1.0 / 256.0,
+            Unit::Resistance => 1.0 / 4096.0,
+            Unit::Time => 5.625,
+            Unit::Power => 0.008 / rsense_milliohms,
+            Unit::Cycles => 0.16,
+        }
+    }
+
+    /// Whether registers in this unit hold twos complement signed values
+    pub fn is_signed(self) -> bool {
+        matches!(self, Unit::Current | Unit::Temperature | Unit::Power)
+    }
+
+    /// Convert a raw register value into this unit, for the given sense
+    /// resistor value in milliohms
+    pub fn convert(self, raw: u16, rsense_milliohms: f32) -> f32 {
+        let value = if self.is_signed() {
+            (raw as i16) as f32
+        } else {
+            raw as f32
+        };
+        value * self.lsb(rsense_milliohms)
+    }
+
+    /// Convert a value in this unit into a raw register value, for the given
+    /// sense resistor value in milliohms, rounding to the nearest LSB.
+    /// Values outside the register's range saturate.
+    pub fn to_raw(self, value: f32, rsense_milliohms: f32) -> u16 {
+        let scaled = crate::round(value / self.lsb(rsense_milliohms));
+        // Float to integer casts saturate
+        if self.is_signed() {
+            (scaled as i16) as u16
+        } else {
+            scaled as u16
+        }
+    }
+}
+
+/// Defines the `Register` enum along with its address, unit and name lookups
+macro_rules! registers {
+    ($($(#[$doc:meta])* $name:ident = $addr:literal, $unit:ident;)*) => {
+        /// A named register in the MAX1720x memory map
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Register {
+            $($(#[$doc])* $name,)*
+        }
+
+        impl Register {
+            /// The 9-bit register address from the datasheet memory map
+            pub const fn address(self) -> u16 {
+                match self {
+                    $(Register::$name => $addr,)*
+                }
+            }
+
+            /// The units of the register contents
+            pub const fn unit(self) -> Unit {
+                match self {
+                    $(Register::$name => Unit::$unit,)*
+                }
+            }
+
+            /// The datasheet name of the register
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Register::$name => stringify!($name),)*
+                }
+            }
+
+            /// Look up a register by its address
+            pub fn from_address(addr: u16) -> Option<Register> {
+                match addr {
+                    $($addr => Some(Register::$name),)*
+                    _ => None,
+                }
+            }
+
+            /// Look up a register by its datasheet name, ignoring case so
+            /// that names such as `nPackCfg` match
+            pub fn from_name(name: &str) -> Option<Register> {
+                $(if name.eq_ignore_ascii_case(stringify!($name)) {
+                    return Some(Register::$name);
+                })*
+                None
+            }
+        }
+    };
+}
+
+registers! {
+    /// Status flags
+    Status = 0x000, Raw;
+    /// Voltage alert thresholds
+    VAlrtTh = 0x001, Raw;
+    /// Temperature alert thresholds
+    TAlrtTh = 0x002, Raw;
+    /// State of charge alert thresholds
+    SAlrtTh = 0x003, Raw;
+    /// Load current used for the AtRate calculations
+    AtRate = 0x004, Current;
+    /// Reported remaining capacity
+    RepCap = 0x005, Capacity;
+    /// Reported state of charge
+    RepSOC = 0x006, Percentage;
+    /// Cell age, FullCapRep as a percentage of DesignCap
+    Age = 0x007, Percentage;
+    /// Temperature
+    Temp = 0x008, Temperature;
+    /// The lowest reading from all cell voltages
+    VCell = 0x009, CellVoltage;
+    /// Instantaneous current
+    Current = 0x00A, Current;
+    /// Average current
+    AvgCurrent = 0x00B, Current;
+    /// Capacity which cannot be drawn at the present load
+    QResidual = 0x00C, Capacity;
+    /// Mixed state of charge before filtering
+    MixSOC = 0x00D, Percentage;
+    /// Available state of charge
+    AvSOC = 0x00E, Percentage;
+    /// Mixed remaining capacity before filtering
+    MixCap = 0x00F, Capacity;
+    /// Full capacity at the present conditions
+    FullCap = 0x010, Capacity;
+    /// Time to empty
+    TTE = 0x011, Time;
+    /// Characterisation table QRTable00
+    QRTable00 = 0x012, Raw;
+    /// State of charge threshold for full detection
+    FullSocThr = 0x013, Percentage;
+    /// Cell internal resistance
+    RCell = 0x014, Resistance;
+    /// Average temperature
+    AvgTA = 0x016, Temperature;
+    /// Charge cycle count
+    Cycles = 0x017, Cycles;
+    /// Design capacity
+    DesignCap = 0x018, Capacity;
+    /// Average of VCell
+    AvgVCell = 0x019, CellVoltage;
+    /// Maximum and minimum temperature
+    MaxMinTemp = 0x01A, Raw;
+    /// Maximum and minimum voltage
+    MaxMinVolt = 0x01B, Raw;
+    /// Maximum and minimum current
+    MaxMinCurr = 0x01C, Raw;
+    /// Configuration
+    Config = 0x01D, Raw;
+    /// Charge termination current
+    IChgTerm = 0x01E, Current;
+    /// Available remaining capacity
+    AvCap = 0x01F, Capacity;
+    /// Time to full
+    TTF = 0x020, Time;
+    /// Device name and revision
+    DevName = 0x021, Raw;
+    /// Characterisation table QRTable10
+    QRTable10 = 0x022, Raw;
+    /// Nominal full capacity
+    FullCapNom = 0x023, Capacity;
+    /// Auxiliary input measurement
+    AIN = 0x027, Ratio;
+    /// Learning configuration
+    LearnCfg = 0x028, Raw;
+    /// Averaging filter configuration
+    FilterCfg = 0x029, Raw;
+    /// Relaxation detection configuration
+    RelaxCfg = 0x02A, Raw;
+    /// Miscellaneous configuration
+    MiscCfg = 0x02B, Raw;
+    /// Thermistor gain
+    TGain = 0x02C, Raw;
+    /// Thermistor offset
+    TOff = 0x02D, Raw;
+    /// Current gain
+    CGain = 0x02E, Raw;
+    /// Current offset
+    COff = 0x02F, Raw;
+    /// Characterisation table QRTable20
+    QRTable20 = 0x032, Raw;
+    /// Die temperature
+    DieTemp = 0x034, Temperature;
+    /// Reported full capacity
+    FullCapRep = 0x035, Capacity;
+    /// Average current at empty
+    IAvgEmpty = 0x036, Current;
+    /// Temperature compensation reference resistance
+    RComp0 = 0x038, Raw;
+    /// Temperature compensation coefficients
+    TempCo = 0x039, Raw;
+    /// Empty and recovery voltage thresholds
+    VEmpty = 0x03A, Raw;
+    /// Fuel gauge status
+    FStat = 0x03D, Raw;
+    /// Elapsed time, LSB = 175.8 ms
+    Timer = 0x03E, Raw;
+    /// Shutdown timer
+    ShdnTimer = 0x03F, Raw;
+    /// Characterisation table QRTable30
+    QRTable30 = 0x042, Raw;
+    /// Resistance gain
+    RGain = 0x043, Raw;
+    /// Accumulated charge for learning
+    DQAcc = 0x045, Capacity;
+    /// Accumulated percentage for learning
+    DPAcc = 0x046, Raw;
+    /// Convergence configuration
+    ConvgCfg = 0x049, Raw;
+    /// Remaining capacity from the voltage fuel gauge
+    VFRemCap = 0x04A, Capacity;
+    /// Raw coulomb count
+    QH = 0x04D, Capacity;
+    /// Command register
+    Command = 0x060, Raw;
+    /// Communication status
+    CommStat = 0x061, Raw;
+    /// Additional status flags
+    Status2 = 0x0B0, Raw;
+    /// Instantaneous power
+    Power = 0x0B1, Power;
+    /// Average power
+    AvgPower = 0x0B3, Power;
+    /// Current alert thresholds
+    IAlrtTh = 0x0B4, Raw;
+    /// Time to full configuration
+    TTFCfg = 0x0B5, Raw;
+    /// Mixing capacity during constant voltage charging
+    CVMixCap = 0x0B6, Capacity;
+    /// Constant voltage charging half time
+    CVHalfTime = 0x0B7, Raw;
+    /// Charge gain temperature coefficient
+    CGTempCo = 0x0B8, Raw;
+    /// Thermistor curve configuration
+    Curve = 0x0B9, Raw;
+    /// Hibernate mode configuration
+    HibCfg = 0x0BA, Raw;
+    /// Configuration 2
+    Config2 = 0x0BB, Raw;
+    /// Cell voltage ripple
+    VRipple = 0x0BC, RippleVoltage;
+    /// Ripple filter configuration
+    RippleCfg = 0x0BD, Raw;
+    /// Elapsed time, LSB = 3.2 hours
+    TimerH = 0x0BE, Raw;
+    /// Average of Cell4
+    AvgCell4 = 0x0D1, CellVoltage;
+    /// Average of Cell3
+    AvgCell3 = 0x0D2, CellVoltage;
+    /// Average of Cell2
+    AvgCell2 = 0x0D3, CellVoltage;
+    /// Average of Cell1
+    AvgCell1 = 0x0D4, CellVoltage;
+    /// Cell 4 voltage
+    Cell4 = 0x0D5, CellVoltage;
+    /// Cell 3 voltage
+    Cell3 = 0x0D6, CellVoltage;
+    /// Cell 2 voltage
+    Cell2 = 0x0D7, CellVoltage;
+    /// Cell 1 voltage
+    Cell1 = 0x0D8, CellVoltage;
+    /// Voltage of the cell stack above the measured cells
+    CellX = 0x0D9, CellVoltage;
+    /// Pack voltage
+    Batt = 0x0DA, PackVoltage;
+    /// Remaining capacity at the AtRate load
+    AtQResidual = 0x0DC, Capacity;
+    /// Time to empty at the AtRate load
+    AtTTE = 0x0DD, Time;
+    /// Available state of charge at the AtRate load
+    AtAvSOC = 0x0DE, Percentage;
+    /// Available capacity at the AtRate load
+    AtAvCap = 0x0DF, Capacity;
+    /// Open circuit voltage estimate
+    VFOCV = 0x0FB, CellVoltage;
+    /// State of charge from the voltage fuel gauge
+    VFSOC = 0x0FF, Percentage;
+    /// SBS recommended charging current, LSB = 1 mA
+    ChargingCurrent = 0x114, Raw;
+    /// SBS recommended charging voltage, LSB = 1 mV
+    ChargingVoltage = 0x115, Raw;
+    /// Voltage to capacity characterisation table
+    NXTable0 = 0x180, Raw;
+    NXTable1 = 0x181, Raw;
+    NXTable2 = 0x182, Raw;
+    NXTable3 = 0x183, Raw;
+    NXTable4 = 0x184, Raw;
+    NXTable5 = 0x185, Raw;
+    NXTable6 = 0x186, Raw;
+    NXTable7 = 0x187, Raw;
+    NXTable8 = 0x188, Raw;
+    NXTable9 = 0x189, Raw;
+    NXTable10 = 0x18A, Raw;
+    NXTable11 = 0x18B, Raw;
+    /// User memory
+    NUser18C = 0x18C, Raw;
+    /// User memory
+    NUser18D = 0x18D, Raw;
+    /// Overdischarge and short circuit thresholds
+    NODSCTh = 0x18E, Raw;
+    /// Overdischarge and short circuit configuration
+    NODSCCfg = 0x18F, Raw;
+    /// Open circuit voltage characterisation table
+    NOCVTable0 = 0x190, Raw;
+    NOCVTable1 = 0x191, Raw;
+    NOCVTable2 = 0x192, Raw;
+    NOCVTable3 = 0x193, Raw;
+    NOCVTable4 = 0x194, Raw;
+    NOCVTable5 = 0x195, Raw;
+    NOCVTable6 = 0x196, Raw;
+    NOCVTable7 = 0x197, Raw;
+    NOCVTable8 = 0x198, Raw;
+    NOCVTable9 = 0x199, Raw;
+    NOCVTable10 = 0x19A, Raw;
+    NOCVTable11 = 0x19B, Raw;
+    /// Charge termination current
+    NIChgTerm = 0x19C, Current;
+    /// Averaging filter configuration
+    NFilterCfg = 0x19D, Raw;
+    /// Empty and recovery voltage thresholds
+    NVEmpty = 0x19E, Raw;
+    /// Learning configuration
+    NLearnCfg = 0x19F, Raw;
+    /// Characterisation table QRTable00
+    NQRTable00 = 0x1A0, Raw;
+    /// Characterisation table QRTable10
+    NQRTable10 = 0x1A1, Raw;
+    /// Characterisation table QRTable20
+    NQRTable20 = 0x1A2, Raw;
+    /// Characterisation table QRTable30
+    NQRTable30 = 0x1A3, Raw;
+    /// Charge cycle count
+    NCycles = 0x1A4, Cycles;
+    /// Nominal full capacity
+    NFullCapNom = 0x1A5, Capacity;
+    /// Temperature compensation reference resistance
+    NRComp0 = 0x1A6, Raw;
+    /// Temperature compensation coefficients
+    NTempCo = 0x1A7, Raw;
+    /// Average current at empty
+    NIAvgEmpty = 0x1A8, Current;
+    /// Reported full capacity
+    NFullCapRep = 0x1A9, Capacity;
+    /// Voltage and temperature history
+    NVoltTemp = 0x1AA, Raw;
+    /// Maximum and minimum current history
+    NMaxMinCurr = 0x1AB, Raw;
+    /// Maximum and minimum voltage history
+    NMaxMinVolt = 0x1AC, Raw;
+    /// Maximum and minimum temperature history
+    NMaxMinTemp = 0x1AD, Raw;
+    /// State of charge history
+    NSOC = 0x1AE, Percentage;
+    /// Elapsed time history
+    NTimerH = 0x1AF, Raw;
+    /// Configuration
+    NConfig = 0x1B0, Raw;
+    /// Ripple filter configuration
+    NRippleCfg = 0x1B1, Raw;
+    /// Miscellaneous configuration
+    NMiscCfg = 0x1B2, Raw;
+    /// Design capacity
+    NDesignCap = 0x1B3, Capacity;
+    /// Hibernate configuration
+    NHibCfg = 0x1B4, Raw;
+    /// Pack configuration
+    NPackCfg = 0x1B5, Raw;
+    /// Relaxation detection configuration
+    NRelaxCfg = 0x1B6, Raw;
+    /// Convergence configuration
+    NConvgCfg = 0x1B7, Raw;
+    /// Nonvolatile memory configuration 0
+    NNVCfg0 = 0x1B8, Raw;
+    /// Nonvolatile memory configuration 1
+    NNVCfg1 = 0x1B9, Raw;
+    /// Nonvolatile memory configuration 2
+    NNVCfg2 = 0x1BA, Raw;
+    /// Smart battery system configuration
+    NSBSCfg = 0x1BB, Raw;
+    /// 64-bit unique ROM ID, word 0
+    NRomID0 = 0x1BC, Raw;
+    /// 64-bit unique ROM ID, word 1
+    NRomID1 = 0x1BD, Raw;
+    /// 64-bit unique ROM ID, word 2
+    NRomID2 = 0x1BE, Raw;
+    /// 64-bit unique ROM ID, word 3
+    NRomID3 = 0x1BF, Raw;
+    /// Voltage alert thresholds
+    NVAlrtTh = 0x1C0, Raw;
+    /// Temperature alert thresholds
+    NTAlrtTh = 0x1C1, Raw;
+    /// State of charge alert thresholds
+    NSAlrtTh = 0x1C2, Raw;
+    /// Current alert thresholds
+    NIAlrtTh = 0x1C3, Raw;
+    /// User memory
+    NUser1C4 = 0x1C4, Raw;
+    /// User memory
+    NUser1C5 = 0x1C5, Raw;
+    /// State of charge threshold for full detection
+    NFullSOCThr = 0x1C6, Percentage;
+    /// Time to full configuration
+    NTTFCfg = 0x1C7, Raw;
+    /// Current gain
+    NCGain = 0x1C8, Raw;
+    /// Thermistor curve
+    NTCurve = 0x1C9, Raw;
+    /// Thermistor gain
+    NTGain = 0x1CA, Raw;
+    /// Thermistor offset
+    NTOff = 0x1CB, Raw;
+    /// Manufacturer name, word 0
+    NManfctrName0 = 0x1CC, Raw;
+    /// Manufacturer name, word 1
+    NManfctrName1 = 0x1CD, Raw;
+    /// Manufacturer name, word 2
+    NManfctrName2 = 0x1CE, Raw;
+    /// Sense resistor value, LSB = 10 μΩ
+    NRSense = 0x1CF, Raw;
+    /// User memory
+    NUser1D0 = 0x1D0, Raw;
+    /// User memory
+    NUser1D1 = 0x1D1, Raw;
+    /// Ageing forecast configuration
+    NAgeFcCfg = 0x1D2, Raw;
+    /// Design voltage
+    NDesignVoltage = 0x1D3, Raw;
+    /// User memory
+    NUser1D4 = 0x1D4, Raw;
+    /// Fast voltage and shutdown configuration
+    NRFastVShdn = 0x1D5, Raw;
+    /// Manufacture date
+    NManfctrDate = 0x1D6, Raw;
+    /// Date of first use
+    NFirstUsed = 0x1D7, Raw;
+    /// Serial number, word 0
+    NSerialNumber0 = 0x1D8, Raw;
+    /// Serial number, word 1
+    NSerialNumber1 = 0x1D9, Raw;
+    /// Serial number, word 2
+    NSerialNumber2 = 0x1DA, Raw;
+    /// Device name, word 0
+    NDeviceName0 = 0x1DB, Raw;
+    /// Device name, word 1
+    NDeviceName1 = 0x1DC, Raw;
+    /// Device name, word 2
+    NDeviceName2 = 0x1DD, Raw;
+    /// Device name, word 3
+    NDeviceName3 = 0x1DE, Raw;
+    /// Device name, word 4
+    NDeviceName4 = 0x1DF, Raw;
+}
+
+impl From<Register> for u16 {
+    fn from(reg: Register) -> u16 {
+        reg.address()
+    }
+}
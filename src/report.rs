@@ -0,0 +1,331 @@
+//! Human-readable reports of the gauge state.
+//!
+//! [`MAX1720x::write_report`] prints the status, measurements, capacities,
+//! alerts and key configuration as plain text, for a shell or a log file:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! max17205.write_report(&mut i2c, &mut std::io::stdout())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ```text
+//! Device
+//!   part: MAX17205/MAX17215
+//!   revision: 0x4005
+//! Status
+//!   raw: 0x0082
+//!   power-on reset: no
+//!   battery present: yes
+//! Measurements
+//!   state of charge: 48.9 %
+//!   pack voltage: 7.412 V
+//! ...
+//! ```
+//!
+//! A value which can't be read is reported in place, so one failing
+//! register doesn't hide the rest of the report.  Only errors from the
+//! writer are returned.
+
+use std::fmt;
+use std::io;
+
+use crate::alert::Alerts;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::variant::PackMeasurement;
+use crate::{MAX1720x, Status};
+
+/// Write one `name: value` line, or the error in place of the value
+fn line<T: fmt::Display, D: fmt::Display>(
+    out: &mut impl io::Write,
+    name: &str,
+    value: Result<T, D>,
+) -> io::Result<()> {
+    match value {
+        Ok(value) => writeln!(out, "  {}: {}", name, value),
+        Err(e) => writeln!(out, "  {}: error: {}", name, e),
+    }
+}
+
+/// "yes" or "no"
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// Format a number of volts, amps or similar with a unit
+fn quantity(value: f32, decimals: usize, unit: &str) -> String {
+    format!("{:.*} {}", decimals, value, unit)
+}
+
+/// Format a list of items, or "none"
+fn list<T: fmt::Debug>(items: impl Iterator<Item = T>) -> String {
+    let items: Vec<String> = items.map(|item| format!("{:?}", item)).collect();
+    if items.is_empty() {
+        "none".into()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// Format a `(min, max)` alert threshold pair, or "disabled"
+fn threshold<T: fmt::Display>(range: Option<(T, T)>, unit: &str) -> String {
+    match range {
+        Some((min, max)) => format!("{} to {} {}", min, max, unit),
+        None => "disabled".into(),
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    E: fmt::Debug,
+    V: PackMeasurement,
+{
+    /// Write a report of the status, measurements, capacities, alerts and
+    /// key configuration to `out`
+    pub fn write_report(&mut self, bus: &mut I2C, out: &mut impl io::Write) -> io::Result<()> {
+        writeln!(out, "Device")?;
+        let revision = self.device_revision(bus);
+        line(
+            out,
+            "part",
+            revision
+                .as_ref()
+                .map(|r| r.part_name().unwrap_or("unknown")),
+        )?;
+        line(
+            out,
+            "revision",
+            revision.map(|r| format!("{:#06x}", r.raw())),
+        )?;
+        writeln!(out, "  driver initialised: {}", yes_no(self.initialised))?;
+
+        writeln!(out, "Status")?;
+        let status = self.read(bus, Register::Status);
+        line(
+            out,
+            "raw",
+            status.as_ref().map(|raw| format!("{:#06x}", raw)),
+        )?;
+        line(
+            out,
+            "power-on reset",
+            status
+                .as_ref()
+                .map(|&raw| yes_no(Status::from_raw(raw).por)),
+        )?;
+        line(
+            out,
+            "battery present",
+            status
+                .as_ref()
+                .map(|&raw| yes_no(!Status::from_raw(raw).bst)),
+        )?;
+        line(
+            out,
+            "data not ready",
+            self.fstat(bus).map(|f| yes_no(f.dnr)),
+        )?;
+        line(out, "hibernating", self.status2(bus).map(|s| yes_no(s.hib)))?;
+
+        writeln!(out, "Measurements")?;
+        line(
+            out,
+            "state of charge",
+            self.state_of_charge(bus).map(|v| quantity(v, 1, "%")),
+        )?;
+        line(
+            out,
+            "pack voltage",
+            self.pack_voltage(bus).map(|v| quantity(v, 3, "V")),
+        )?;
+        line(
+            out,
+            "cell voltages",
+            self.cell_voltages(bus).map(|cells| {
+                let cells: Vec<String> = cells.iter().map(|&v| quantity(v, 3, "V")).collect();
+                cells.join(", ")
+            }),
+        )?;
+        line(
+            out,
+            "current",
+            self.current(bus).map(|v| quantity(v, 3, "A")),
+        )?;
+        line(
+            out,
+            "average current",
+            self.read_measurement(bus, Register::AvgCurrent)
+                .map(|v| quantity(v, 3, "A")),
+        )?;
+        line(
+            out,
+            "temperature",
+            self.temperature(bus).map(|v| quantity(v, 1, "°C")),
+        )?;
+        let minutes = |time: Option<core::time::Duration>| match time {
+            Some(time) => quantity(time.as_secs_f32() / 60.0, 0, "min"),
+            None => "n/a".into(),
+        };
+        line(out, "time to empty", self.time_to_empty(bus).map(minutes))?;
+        line(out, "time to full", self.time_to_full(bus).map(minutes))?;
+
+        writeln!(out, "Capacity")?;
+        match self.capacity_estimates(bus) {
+            Ok(estimates) => {
+                writeln!(
+                    out,
+                    "  reported: {}",
+                    quantity(estimates.reported, 1, "mAh")
+                )?;
+                writeln!(out, "  mixed: {}", quantity(estimates.mixed, 1, "mAh"))?;
+                writeln!(
+                    out,
+                    "  voltage fuel gauge: {}",
+                    quantity(estimates.voltage_fuel_gauge, 1, "mAh")
+                )?;
+                writeln!(
+                    out,
+                    "  residual: {}",
+                    quantity(estimates.residual, 1, "mAh")
+                )?;
+            }
+            Err(e) => writeln!(out, "  remaining: error: {}", e)?,
+        }
+        line(
+            out,
+            "full",
+            self.full_capacity(bus).map(|v| quantity(v, 1, "mAh")),
+        )?;
+        line(
+            out,
+            "design",
+            self.design_capacity(bus).map(|v| quantity(v, 1, "mAh")),
+        )?;
+        line(
+            out,
+            "state of health",
+            self.state_of_health(bus).map(|v| quantity(v, 1, "%")),
+        )?;
+        line(
+            out,
+            "cycles",
+            self.read_converted(bus, Register::Cycles)
+                .map(|v| format!("{:.2}", v)),
+        )?;
+
+        writeln!(out, "Alerts")?;
+        line(
+            out,
+            "active",
+            status.map(|raw| list(Alerts::from_status(raw).iter())),
+        )?;
+        match self.alert_thresholds(bus) {
+            Ok(thresholds) => {
+                writeln!(out, "  voltage: {}", threshold(thresholds.voltage, "V"))?;
+                writeln!(
+                    out,
+                    "  temperature: {}",
+                    threshold(thresholds.temperature, "°C")
+                )?;
+                writeln!(out, "  state of charge: {}", threshold(thresholds.soc, "%"))?;
+                writeln!(out, "  current: {}", threshold(thresholds.current, "A"))?;
+            }
+            Err(e) => writeln!(out, "  thresholds: error: {}", e)?,
+        }
+
+        writeln!(out, "Configuration")?;
+        writeln!(
+            out,
+            "  sense resistor: {}",
+            quantity(self.rsense_milliohms, 1, "mΩ")
+        )?;
+        line(
+            out,
+            "Config",
+            self.read(bus, Register::Config)
+                .map(|raw| format!("{:#06x}", raw)),
+        )?;
+        line(
+            out,
+            "nPackCfg",
+            self.read(bus, Register::NPackCfg)
+                .map(|raw| format!("{:#06x}", raw)),
+        )?;
+        line(
+            out,
+            "gauging mode",
+            self.gauging_mode(bus).map(|mode| match mode {
+                Some(mode) => format!("{:?}", mode),
+                None => "custom".into(),
+            }),
+        )?;
+        line(
+            out,
+            "shutdown timeout",
+            self.shutdown_timeout(bus)
+                .map(|t| quantity(t.as_secs_f32(), 0, "s")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::MockMax1720x;
+    use crate::registers::Register;
+    use crate::MAX1720x;
+
+    fn report(mock: &mut MockMax1720x, fail: usize) -> String {
+        let mut gauge: MAX1720x<_, _> = MAX1720x::new(mock);
+        gauge.init(mock).unwrap();
+        mock.fail_next(fail);
+        let mut out = Vec::new();
+        gauge.write_report(mock, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn sections() {
+        let mut mock = MockMax1720x::new();
+        mock.set_register(Register::DesignCap.address(), 0x1770);
+        mock.set_register(Register::FullCapNom.address(), 0x1770);
+        let text = report(&mut mock, 0);
+        let headers: Vec<&str> = text.lines().filter(|l| !l.starts_with(' ')).collect();
+        assert_eq!(
+            headers,
+            [
+                "Device",
+                "Status",
+                "Measurements",
+                "Capacity",
+                "Alerts",
+                "Configuration"
+            ]
+        );
+        assert!(!text.contains("error:"), "{}", text);
+    }
+
+    #[test]
+    fn failed_read_is_reported_inline() {
+        let text = report(&mut MockMax1720x::new(), 1);
+        assert!(text.contains("\n  part: error: "), "{}", text);
+        assert!(text.contains("\n  state of charge: 50.0 %\n"), "{}", text);
+        assert!(text.contains("\nConfiguration\n"), "{}", text);
+    }
+}
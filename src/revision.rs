@@ -0,0 +1,121 @@
+//! Part and silicon revision identification.
+//!
+//! The DevName register identifies the part in its low nibble and the
+//! silicon revision in the bits above.  Boards built with different
+//! revisions can read it at runtime and adapt:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! let revision = max17205.device_revision(&mut i2c)?;
+//! println!("{} rev {}", revision.part_name().unwrap_or("unknown"), revision.revision());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The driver caches the value after the first read, so checking it again
+//! costs nothing.  The driver uses it to adapt to the connected part: the
+//! per-cell measurements and cell balancing of a multi-cell pack return
+//! [`Error::Unsupported`] on the single-cell MAX17201/MAX17211, which
+//! [`DeviceRevision::supports`] checks ahead of time.  No silicon
+//! revision-specific errata are known to the driver; workarounds for them
+//! belong in [`DeviceRevision::supports`], gated on
+//! [`DeviceRevision::is_at_least`].
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::{Error, MAX1720x};
+
+/// A feature which only some parts or silicon revisions have
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// Measuring each cell of a multi-cell pack in Cell1 - Cell4
+    CellVoltages,
+    /// Balancing the cells of a multi-cell pack
+    Balancing,
+}
+
+/// The contents of the DevName register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceRevision(u16);
+
+impl DeviceRevision {
+    /// Decode a raw DevName value
+    pub const fn from_raw(raw: u16) -> Self {
+        DeviceRevision(raw)
+    }
+
+    /// The raw DevName value
+    pub const fn raw(self) -> u16 {
+        self.0
+    }
+
+    /// The part code, from the low nibble
+    pub const fn part_code(self) -> u8 {
+        (self.0 & 0xF) as u8
+    }
+
+    /// The silicon revision, from the bits above the part code
+    pub const fn revision(self) -> u16 {
+        self.0 >> 4
+    }
+
+    /// Whether the silicon is revision `revision` or later
+    pub const fn is_at_least(self, revision: u16) -> bool {
+        self.revision() >= revision
+    }
+
+    /// Whether the part and revision have `feature`.  Parts which aren't
+    /// recognised are assumed to have everything.
+    pub const fn supports(self, feature: Feature) -> bool {
+        match feature {
+            // The MAX17201/MAX17211 only measure a single cell
+            Feature::CellVoltages | Feature::Balancing => self.part_code() != 0x1,
+        }
+    }
+
+    /// The parts which report this part code.  The I2C and 1-Wire versions
+    /// of a part can't be told apart.
+    pub const fn part_name(self) -> Option<&'static str> {
+        match self.part_code() {
+            0x1 => Some("MAX17201/MAX17211"),
+            0x5 => Some("MAX17205/MAX17215"),
+            _ => None,
+        }
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Get the part and silicon revision from DevName.  The register is
+    /// only read the first time.
+    pub fn device_revision(&mut self, bus: &mut I2C) -> Result<DeviceRevision, Error<E>> {
+        if let Some(revision) = self.revision {
+            return Ok(revision);
+        }
+        let revision = DeviceRevision::from_raw(self.read(bus, Register::DevName)?);
+        self.revision = Some(revision);
+        Ok(revision)
+    }
+
+    /// Check whether the connected part has `feature`, returning
+    /// [`Error::Unsupported`] if it doesn't
+    pub(crate) fn require(&mut self, bus: &mut I2C, feature: Feature) -> Result<(), Error<E>> {
+        if self.device_revision(bus)?.supports(feature) {
+            Ok(())
+        } else {
+            Err(Error::Unsupported)
+        }
+    }
+}
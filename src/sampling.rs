@@ -0,0 +1,138 @@
+//! Repeated sampling of a measurement.
+//!
+//! [`MAX1720x::sample`] reads a measurement register a number of times and
+//! returns the mean, extremes and standard deviation, for steadying a
+//! displayed value or characterising noise:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::registers::Register;
+//! # use max1720x::sampling::SAMPLE_PERIOD_MS;
+//! let current = max17205.sample(&mut i2c, &mut delay, Register::Current, 32, SAMPLE_PERIOD_MS)?;
+//! println!("{} A ± {} A", current.mean, current.std_dev);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The gauge updates its measurement registers every 175.8 ms, so samples
+//! taken more often than [`SAMPLE_PERIOD_MS`] repeat the same reading.
+
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::variant::PackMeasurement;
+use crate::{Error, MAX1720x};
+
+/// Interval in ms between updates of the measurement registers, rounded up
+pub const SAMPLE_PERIOD_MS: u32 = 176;
+
+/// Statistics of a set of samples, from [`MAX1720x::sample`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleStats {
+    /// Number of samples
+    pub count: usize,
+    /// Mean of the samples
+    pub mean: f32,
+    /// Smallest sample
+    pub min: f32,
+    /// Largest sample
+    pub max: f32,
+    /// Population standard deviation of the samples
+    pub std_dev: f32,
+}
+
+/// Square root by Newton's method, as `f32::sqrt` needs `std`
+fn sqrt(value: f32) -> f32 {
+    if value <= 0.0 {
+        return 0.0;
+    }
+    let mut root = if value > 1.0 { value } else { 1.0 };
+    for _ in 0..32 {
+        let next = (root + value / root) / 2.0;
+        if next >= root {
+            break;
+        }
+        root = next;
+    }
+    root
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Read the measurement register `reg` `count` times, waiting
+    /// `interval_ms` between reads, and return statistics of the readings
+    /// in the register's units.  Returns [`Error::InvalidArgument`] if
+    /// `count` is 0.
+    pub fn sample<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        reg: Register,
+        count: usize,
+        interval_ms: u32,
+    ) -> Result<SampleStats, Error<E>> {
+        if count == 0 {
+            return Err(Error::InvalidArgument);
+        }
+        // Welford's running mean and variance
+        let mut mean = 0.0;
+        let mut m2 = 0.0;
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        for n in 1..=count {
+            if n > 1 {
+                delay.delay_ms(interval_ms);
+            }
+            let value = self.read_measurement(bus, reg)?;
+            let delta = value - mean;
+            mean += delta / n as f32;
+            m2 += delta * (value - mean);
+            min = min.min(value);
+            max = max.max(value);
+        }
+        Ok(SampleStats {
+            count,
+            mean,
+            min,
+            max,
+            std_dev: sqrt(m2 / count as f32),
+        })
+    }
+
+    /// Sample the pack current, as [`sample`](Self::sample), once per
+    /// register update
+    pub fn sample_current<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        count: usize,
+    ) -> Result<SampleStats, Error<E>> {
+        self.sample(bus, delay, Register::Current, count, SAMPLE_PERIOD_MS)
+    }
+
+    /// Sample the pack voltage, as [`sample`](Self::sample), once per
+    /// register update
+    pub fn sample_pack_voltage<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        count: usize,
+    ) -> Result<SampleStats, Error<E>>
+    where
+        V: PackMeasurement,
+    {
+        self.sample(bus, delay, Register::Batt, count, SAMPLE_PERIOD_MS)
+    }
+}
@@ -0,0 +1,122 @@
+//! Charge transferred over a charge or discharge session.
+//!
+//! A [`ChargeSession`] records the coulomb counter (QH), the reported
+//! remaining capacity and the gauge timer at the start of a session, and
+//! reports the difference when it is finished:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # struct Charger;
+//! # impl Charger {
+//! #     fn run_until_full(&mut self) {}
+//! # }
+//! # let mut charger = Charger;
+//! let session = max17205.start_charge_session(&mut i2c)?;
+//! charger.run_until_full();
+//! let report = session.finish(&mut max17205, &mut i2c)?;
+//! println!("{} mAh in {:?}", report.charge, report.duration);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Comparing the charge counted into the pack with the charge the charger
+//! delivered gives the charging efficiency, and comparing it with the
+//! change in RepCap shows how well the model tracks the pack.
+
+use core::time::Duration;
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::{Register, Unit};
+use crate::{decode, Error, MAX1720x};
+
+/// A session started with [`MAX1720x::start_charge_session`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChargeSession {
+    qh: u16,
+    rep_cap: f32,
+    uptime: Duration,
+}
+
+/// The result of a [`ChargeSession`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionReport {
+    /// Charge counted into the pack in mAh, negative when discharging
+    pub charge: f32,
+    /// Change in the reported remaining capacity (RepCap) in mAh
+    pub rep_cap_change: f32,
+    /// Average current over the session in amps
+    pub average_current: f32,
+    /// Length of the session, from the gauge timer
+    pub duration: Duration,
+}
+
+impl ChargeSession {
+    /// Finish the session and report the charge transferred since it
+    /// started.
+    ///
+    /// QH is a 16-bit counter which wraps, so the charge is only correct if
+    /// less than half of its range was transferred: about 16 Ah with a
+    /// 10 mΩ sense resistor.
+    pub fn finish<I2C, E, const CELLS: usize, V>(
+        self,
+        gauge: &mut MAX1720x<I2C, E, CELLS, V>,
+        bus: &mut I2C,
+    ) -> Result<SessionReport, Error<E>>
+    where
+        I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    {
+        if !gauge.initialised {
+            return Err(Error::NotInitialised);
+        }
+        let [qh, rep_cap] = gauge.read_coherent(bus, [Register::QH, Register::RepCap])?;
+        let rep_cap = decode::value(Register::RepCap, rep_cap, gauge.rsense_milliohms());
+        let uptime = gauge.uptime(bus)?;
+
+        // The difference of the wrapping counter, as a signed count
+        let delta = qh.wrapping_sub(self.qh) as i16;
+        let charge = delta as f32 * Unit::Capacity.lsb(gauge.rsense_milliohms());
+        let duration = uptime.saturating_sub(self.uptime);
+        let hours = duration.as_secs_f32() / 3600.0;
+        let average_current = if hours > 0.0 {
+            charge / hours / 1000.0
+        } else {
+            0.0
+        };
+        Ok(SessionReport {
+            charge,
+            rep_cap_change: rep_cap - self.rep_cap,
+            average_current,
+            duration,
+        })
+    }
+}
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Start measuring the charge transferred, for example over one charge
+    /// cycle.  The session doesn't borrow the driver, which can be used as
+    /// normal until [`ChargeSession::finish`].
+    pub fn start_charge_session(&mut self, bus: &mut I2C) -> Result<ChargeSession, Error<E>> {
+        if !self.initialised {
+            return Err(Error::NotInitialised);
+        }
+        let [qh, rep_cap] = self.read_coherent(bus, [Register::QH, Register::RepCap])?;
+        Ok(ChargeSession {
+            qh,
+            rep_cap: decode::value(Register::RepCap, rep_cap, self.rsense_milliohms),
+            uptime: self.uptime(bus)?,
+        })
+    }
+}
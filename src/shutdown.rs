@@ -0,0 +1,103 @@
+//! Shutdown mode.
+//!
+//! In shutdown the gauge stops measuring and draws almost no current, for
+//! shipping or storing a product for long periods.  The gauge shuts down
+//! once the ShdnTimer timeout has passed after [`MAX1720x::shutdown`], or
+//! after the I2C lines have been held low for the timeout if
+//! [`MAX1720x::set_comm_shutdown`] is enabled:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use core::time::Duration;
+//! max17205.set_shutdown_timeout(&mut i2c, Duration::from_secs(45))?;
+//! max17205.shutdown(&mut i2c)?;
+//!
+//! // Later, after a rising edge on SDA or SCL has woken the gauge
+//! max17205.init_blocking(&mut i2c, &mut delay)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The gauge wakes on a rising edge on SDA or SCL, so the first bus access
+//! after shutdown wakes it, but that access itself may not be acknowledged.
+//! Waking restarts the gauge with the power-on register values reloaded
+//! from nonvolatile memory, so any volatile settings need to be written
+//! again.  The gauge estimates the state of charge afresh from the cell
+//! voltage, and charge moved while it was shut down isn't counted.
+
+use core::time::Duration;
+
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::registers::Register;
+use crate::{Error, MAX1720x};
+
+/// Config.SHDN: shut down after the ShdnTimer timeout
+const CONFIG_SHDN: u16 = 1 << 7;
+
+/// Config.COMMSH: shut down when SDA and SCL are held low for the ShdnTimer
+/// timeout
+const CONFIG_COMMSH: u16 = 1 << 6;
+
+/// Shift of ShdnTimer.THR, bits 15:13
+const THR_SHIFT: u16 = 13;
+
+/// Shortest shutdown timeout, with THR = 0, in milliseconds: 175.8 ms ×
+/// 2^8
+const MIN_TIMEOUT_MS: u64 = 45_000;
+
+/// Largest value of ShdnTimer.THR
+const MAX_THR: u16 = 7;
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Shut the gauge down once the shutdown timeout has passed.
+    ///
+    /// The driver needs [`init`](Self::init) again after the gauge wakes,
+    /// so it is marked uninitialised straight away.
+    pub fn shutdown(&mut self, bus: &mut I2C) -> Result<(), Error<E>> {
+        self.modify(bus, Register::Config, CONFIG_SHDN, CONFIG_SHDN)?;
+        self.initialised = false;
+        Ok(())
+    }
+
+    /// Enable or disable shutting down when both I2C lines are held low for
+    /// the shutdown timeout (Config.COMMSH), so that a host can put the
+    /// gauge to sleep by cutting power to the bus pull-ups
+    pub fn set_comm_shutdown(&mut self, bus: &mut I2C, enable: bool) -> Result<(), Error<E>> {
+        let value = if enable { CONFIG_COMMSH } else { 0 };
+        self.modify(bus, Register::Config, CONFIG_COMMSH, value)
+    }
+
+    /// Get the delay before the gauge shuts down, from ShdnTimer.THR
+    pub fn shutdown_timeout(&mut self, bus: &mut I2C) -> Result<Duration, Error<E>> {
+        let thr = self.read(bus, Register::ShdnTimer)? >> THR_SHIFT;
+        Ok(Duration::from_millis(MIN_TIMEOUT_MS << thr))
+    }
+
+    /// Set the delay before the gauge shuts down.  The available timeouts
+    /// double from 45 s up to 96 minutes; `timeout` is rounded up to the
+    /// next one, and anything longer returns [`Error::InvalidArgument`].
+    pub fn set_shutdown_timeout(
+        &mut self,
+        bus: &mut I2C,
+        timeout: Duration,
+    ) -> Result<(), Error<E>> {
+        let ms = timeout.as_millis();
+        let thr = (0..=MAX_THR)
+            .find(|&thr| ms <= (MIN_TIMEOUT_MS << thr) as u128)
+            .ok_or(Error::InvalidArgument)?;
+        self.write_register(bus, Register::ShdnTimer.address(), thr << THR_SHIFT)
+    }
+}
@@ -0,0 +1,217 @@
+//! Statistics over a charge or discharge session.
+//!
+//! [`SessionStats`] is fed [`Measurements`] periodically, each stamped with
+//! the time since an arbitrary start, and keeps the extremes, the average
+//! current and the energy into and out of the pack without storing the
+//! samples:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::stats::SessionStats;
+//! # let mut clock = || 0;
+//! # fn upload<T>(_summary: &T) {}
+//! let mut stats = SessionStats::new();
+//!
+//! // Every few seconds while charging
+//! stats.record_now(&mut max17205, &mut i2c, &mut clock)?;
+//!
+//! // When the charger stops
+//! if let Some(summary) = stats.summary() {
+//!     upload(&summary);
+//! }
+//! stats.reset();
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Energy and the average current are integrated between samples, so they
+//! are only as accurate as the sampling is frequent.  For the charge moved
+//! the gauge's own coulomb counter in [`session`](crate::session) is more
+//! accurate.
+
+use core::time::Duration;
+
+use crate::clock::Clock;
+use crate::energy::EnergyMeter;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::variant::PackMeasurement;
+use crate::{Error, MAX1720x, Measurements};
+
+/// Summary of a session, from [`SessionStats::summary`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionSummary {
+    /// Number of samples recorded
+    pub samples: u32,
+    /// Time from the first sample to the last
+    pub duration: Duration,
+    /// Lowest pack voltage in volts
+    pub min_voltage: f32,
+    /// Highest pack voltage in volts
+    pub max_voltage: f32,
+    /// Current of the largest magnitude seen, in amps, positive when
+    /// charging
+    pub peak_current: f32,
+    /// Average current over the session in amps, positive when charging
+    pub average_current: f32,
+    /// Lowest temperature in degrees Celsius
+    pub min_temperature: f32,
+    /// Highest temperature in degrees Celsius
+    pub max_temperature: f32,
+    /// Energy put into the pack in watt hours
+    pub energy_in: f32,
+    /// Energy taken out of the pack in watt hours
+    pub energy_out: f32,
+}
+
+/// The last sample, needed to integrate up to the next one
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Previous {
+    at: Duration,
+    current: f32,
+}
+
+/// Accumulates statistics over a session of periodic measurements
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionStats {
+    start: Duration,
+    previous: Option<Previous>,
+    summary: SessionSummary,
+    /// Integral of the current over time, in amp seconds
+    charge: f32,
+    energy: EnergyMeter,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionStats {
+    /// Start an empty session
+    pub const fn new() -> Self {
+        SessionStats {
+            start: Duration::ZERO,
+            previous: None,
+            summary: SessionSummary {
+                samples: 0,
+                duration: Duration::ZERO,
+                min_voltage: 0.0,
+                max_voltage: 0.0,
+                peak_current: 0.0,
+                average_current: 0.0,
+                min_temperature: 0.0,
+                max_temperature: 0.0,
+                energy_in: 0.0,
+                energy_out: 0.0,
+            },
+            charge: 0.0,
+            energy: EnergyMeter::new(),
+        }
+    }
+
+    /// Discard everything recorded so far, to start a new session
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Add measurements taken at time `at`.  Times must not go backwards;
+    /// a sample from before the previous one adds nothing to the energy or
+    /// average current.
+    pub fn add(&mut self, at: Duration, measurements: &Measurements) {
+        let Measurements {
+            voltage,
+            current,
+            temperature,
+            ..
+        } = *measurements;
+        let s = &mut self.summary;
+
+        match self.previous {
+            None => {
+                self.start = at;
+                s.min_voltage = voltage;
+                s.max_voltage = voltage;
+                s.peak_current = current;
+                s.min_temperature = temperature;
+                s.max_temperature = temperature;
+            }
+            Some(previous) => {
+                s.min_voltage = s.min_voltage.min(voltage);
+                s.max_voltage = s.max_voltage.max(voltage);
+                if current.abs() > s.peak_current.abs() {
+                    s.peak_current = current;
+                }
+                s.min_temperature = s.min_temperature.min(temperature);
+                s.max_temperature = s.max_temperature.max(temperature);
+
+                // Trapezoidal integration since the previous sample
+                let seconds = at.saturating_sub(previous.at).as_secs_f32();
+                self.charge += (previous.current + current) / 2.0 * seconds;
+            }
+        }
+        self.energy.add(at, voltage * current);
+        let energy = self.energy.totals();
+        s.energy_in = energy.energy_in;
+        s.energy_out = energy.energy_out;
+
+        s.samples += 1;
+        s.duration = at.saturating_sub(self.start);
+        let seconds = s.duration.as_secs_f32();
+        s.average_current = if seconds > 0.0 {
+            self.charge / seconds
+        } else {
+            current
+        };
+        self.previous = Some(Previous { at, current });
+    }
+
+    /// Read the measurements from a gauge and add them, stamped with `at`
+    pub fn record<I2C, E, const CELLS: usize, V>(
+        &mut self,
+        gauge: &mut MAX1720x<I2C, E, CELLS, V>,
+        bus: &mut I2C,
+        at: Duration,
+    ) -> Result<(), Error<E>>
+    where
+        I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+        V: PackMeasurement,
+    {
+        let measurements = gauge.measurements(bus)?;
+        self.add(at, &measurements);
+        Ok(())
+    }
+
+    /// Read the measurements from a gauge and add them, stamped with the
+    /// time from `clock`
+    pub fn record_now<I2C, E, const CELLS: usize, V, C>(
+        &mut self,
+        gauge: &mut MAX1720x<I2C, E, CELLS, V>,
+        bus: &mut I2C,
+        clock: &mut C,
+    ) -> Result<(), Error<E>>
+    where
+        I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+        V: PackMeasurement,
+        C: Clock,
+    {
+        let measurements = gauge.measurements_at(bus, clock)?;
+        self.add(measurements.at(), &measurements.value);
+        Ok(())
+    }
+
+    /// The statistics so far, or `None` if nothing has been recorded
+    pub fn summary(&self) -> Option<SessionSummary> {
+        self.previous.map(|_| self.summary)
+    }
+}
@@ -0,0 +1,99 @@
+//! Handling a battery being removed and a new one inserted.
+//!
+//! With a removable pack the gauge stays powered from the host while the
+//! battery is swapped, so it carries on from the old battery's state.  The
+//! Bi and Br flags in the STATUS register record the swap, and
+//! [`MAX1720x::handle_battery_swap`] restarts the gauge for the new battery
+//! when they are set.  It can be polled, or called when ALRT is asserted
+//! with the battery insertion and removal alerts enabled:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # use max1720x::swap::BatterySwap;
+//! # use max1720x::variant::Max17055;
+//! # let mut max17055 = MAX1720x::<_, _, 1, Max17055>::with_cells(&mut i2c);
+//! # max17055.init(&mut i2c)?;
+//! match max17055.handle_battery_swap(&mut i2c, &mut delay, None)? {
+//!     BatterySwap::Inserted => println!("new battery at {}%", max17055.state_of_charge(&mut i2c)?),
+//!     BatterySwap::Removed => println!("battery removed"),
+//!     BatterySwap::NoChange => {}
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::alert::Alert;
+use crate::hal::blocking::delay::DelayMs;
+use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::learned::LearnedParams;
+use crate::registers::Register;
+use crate::{Error, MAX1720x};
+
+/// What [`MAX1720x::handle_battery_swap`] found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatterySwap {
+    /// Neither Bi nor Br was set
+    NoChange,
+    /// The battery was removed and no battery is present now
+    Removed,
+    /// A battery was inserted and the gauge has been restarted for it
+    Inserted,
+}
+
+/// Status.Bst: set when no battery is present
+const STATUS_BST: u16 = 1 << 3;
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V>
+where
+    I2C: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Check for a battery swap and, if a battery has been inserted, restart
+    /// the gauge for it:
+    ///
+    /// 1. Reset the fuel gauge, so that the state of charge is estimated
+    ///    afresh from the new cell's voltage.
+    /// 2. Wait for the first measurements and initialise the driver, as
+    ///    [`init_blocking`](Self::init_blocking).
+    /// 3. Restore `learned`, if given, for example parameters saved from
+    ///    this pack or characterised for the cell model.  Otherwise the
+    ///    gauge starts from the configuration in nonvolatile memory.
+    /// 4. Clear Bi and Br.
+    ///
+    /// While no battery is present the driver is left uninitialised.
+    /// Volatile settings such as the alert thresholds are reloaded by the
+    /// reset and need writing again after [`BatterySwap::Inserted`].
+    pub fn handle_battery_swap<D: DelayMs<u32>>(
+        &mut self,
+        bus: &mut I2C,
+        delay: &mut D,
+        learned: Option<&LearnedParams>,
+    ) -> Result<BatterySwap, Error<E>> {
+        let flags = Alert::BatteryInserted.bit() | Alert::BatteryRemoved.bit();
+        let status = self.read(bus, Register::Status)?;
+        if status & flags == 0 {
+            return Ok(BatterySwap::NoChange);
+        }
+
+        let swap = if status & STATUS_BST != 0 {
+            self.initialised = false;
+            BatterySwap::Removed
+        } else {
+            self.reset_fuel_gauge(bus)?;
+            self.init_blocking(bus, delay)?;
+            if let Some(params) = learned {
+                self.restore_learned_params(bus, params)?;
+            }
+            BatterySwap::Inserted
+        };
+        self.modify(bus, Register::Status, flags, 0)?;
+        Ok(swap)
+    }
+}
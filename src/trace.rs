@@ -0,0 +1,145 @@
+//! Tracing of register accesses.
+//!
+//! Only built with the `trace` feature, so tracing costs nothing when it
+//! isn't used.  A trace hook set with [`MAX1720x::set_trace`] is called
+//! with every register value read from or written to the device, so the
+//! raw bus traffic can be logged without a bus analyser.  The hook is a
+//! closure, so it can keep state such as a counter or a buffer, or
+//! forward to whichever logging framework the application uses, for
+//! example `log::trace!("{}", t)`:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::trace::Trace;
+//! let mut count = 0;
+//! max17205.set_trace(Some(Box::leak(Box::new(move |trace: &Trace| {
+//!     count += 1;
+//!     println!("{} {}", count, trace);
+//! }))));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Trace`] implements `Display`, giving lines such as
+//! `read 0x006 RepSOC = 0x3200 (50)`.
+
+use core::fmt;
+
+use crate::registers::{Register, Unit};
+use crate::{decode, MAX1720x};
+
+/// Whether a register was read or written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Read from the device
+    Read,
+    /// Written to the device
+    Write,
+}
+
+/// One register access, passed to the trace hook
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trace {
+    /// Whether the register was read or written
+    pub access: Access,
+    /// The 9-bit register address
+    pub addr: u16,
+    /// The raw register value
+    pub value: u16,
+    /// The register, if it is a known one
+    pub register: Option<Register>,
+    /// The value in the register's units, if it has any
+    pub converted: Option<f32>,
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = match self.access {
+            Access::Read => "read",
+            Access::Write => "write",
+        };
+        write!(f, "{} {:#05x}", access, self.addr)?;
+        if let Some(register) = self.register {
+            write!(f, " {}", register.name())?;
+        }
+        write!(f, " = {:#06x}", self.value)?;
+        if let Some(converted) = self.converted {
+            write!(f, " ({})", converted)?;
+        }
+        Ok(())
+    }
+}
+
+/// A closure called with every register access.  It needs a `'static`
+/// lifetime, which can come from `Box::leak` or a `static_cell`.
+pub type TraceHook = &'static mut (dyn FnMut(&Trace) + Send);
+
+impl<I2C, E, const CELLS: usize, V> MAX1720x<I2C, E, CELLS, V> {
+    /// Set a closure to be called with every register value read or
+    /// written, or `None` to stop tracing.  Shadow RAM writes are traced
+    /// for each attempt, along with the read back to verify them.
+    pub fn set_trace(&mut self, hook: Option<TraceHook>) {
+        self.trace = hook;
+    }
+
+    /// Pass a register access to the trace hook, if there is one
+    pub(crate) fn trace(&mut self, access: Access, addr: u16, value: u16) {
+        let Some(hook) = self.trace.as_mut() else {
+            return;
+        };
+        let register = Register::from_address(addr);
+        let converted = register
+            .filter(|reg| reg.unit() != Unit::Raw)
+            .map(|reg| decode::value(reg, value, self.rsense_milliohms));
+        hook(&Trace {
+            access,
+            addr,
+            value,
+            register,
+            converted,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::boxed::Box;
+    use std::sync::{Arc, Mutex};
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::mock::MockMax1720x;
+
+    #[test]
+    fn hook_keeps_state() {
+        let mut mock = MockMax1720x::new();
+        let mut gauge: MAX1720x<_, _> = MAX1720x::new(&mut mock);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        gauge.set_trace(Some(Box::leak(Box::new(move |t: &Trace| {
+            log.lock().unwrap().push((t.access, t.addr, t.value));
+        }))));
+        gauge.write_register(&mut mock, 0x0b4, 0x1234).unwrap();
+        let value = gauge.read_register(&mut mock, 0x0b4).unwrap();
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [(Access::Write, 0x0b4, 0x1234), (Access::Read, 0x0b4, value)]
+        );
+
+        gauge.set_trace(None);
+        gauge.read_register(&mut mock, 0x0b4).unwrap();
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,151 @@
+//! Marker types for the parts in the MAX1720x family.
+//!
+//! The variant is the last type parameter of [`MAX1720x`](crate::MAX1720x).
+//! Naming the exact part means APIs which only apply to some parts, such as
+//! cell balancing on the MAX17205/MAX17215, are only available when they are
+//! supported, and a pack configuration with more cells than the part can
+//! measure fails to compile:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # use max1720x::variant::Max17205;
+//! # use max1720x::BalancingThreshold;
+//! let mut gauge = MAX1720x::<_, _, 3, Max17205>::with_cells(&mut i2c);
+//! gauge.set_balancing(&mut i2c, BalancingThreshold::Mv10)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Generic`] is the default and allows every MAX1720x API, for code which
+//! doesn't know which of those parts it is talking to.
+//!
+//! The MAX17211 and MAX17215 have a 1-Wire interface rather than I2C, so
+//! using them with this driver requires a bus adapter implementing the
+//! embedded-hal I2C traits on top of 1-Wire memory accesses.
+//!
+//! The MAX17055 and MAX17262 share the ModelGauge m5 core and the register
+//! map from 0x000 to 0x0FF, but have no nonvolatile memory and only answer
+//! on I2C address 0x36.  With these variants the nonvolatile configuration,
+//! history, identity and provisioning APIs are unavailable, and the gauge
+//! must be configured through the volatile registers after every power on.
+//! They have no Batt or Cell1 - Cell4 registers either, so the pack and
+//! cell voltage APIs are unavailable and the cell voltage is read with
+//! [`lowest_cell_voltage`](crate::MAX1720x::lowest_cell_voltage).
+//!
+//! The MAX17301 and MAX17311 add battery protection, with the APIs in
+//! [`protector`](crate::protector).  Their nonvolatile memory is laid out
+//! differently from the MAX1720x, so the nonvolatile APIs are unavailable
+//! for them too, as are the SBS charging registers, which the protector
+//! registers replace.
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A part in the MAX1720x family
+pub trait Variant: sealed::Sealed {
+    /// Part name
+    const NAME: &'static str;
+    /// Maximum number of cells which the part measures individually
+    const MAX_CELLS: usize;
+}
+
+/// Parts which can balance the cells in a multi-cell pack
+pub trait Balancing: Variant {}
+
+/// Parts with nonvolatile configuration memory at 0x180 - 0x1DF
+pub trait NonVolatile: Variant {}
+
+/// Parts with integrated battery protection
+pub trait Protector: Variant {}
+
+/// Parts which measure the pack voltage in Batt and each cell in Cell1 -
+/// Cell4
+pub trait PackMeasurement: Variant {}
+
+/// Parts with the Smart Battery System ChargingCurrent and ChargingVoltage
+/// registers at 0x114 - 0x115
+pub trait SmartBattery: Variant {}
+
+/// Any MAX1720x part, with every MAX1720x API available
+pub struct Generic;
+
+/// MAX17201: single cell, I2C
+pub struct Max17201;
+
+/// MAX17205: multi-cell with balancing, I2C
+pub struct Max17205;
+
+/// MAX17211: single cell, 1-Wire
+pub struct Max17211;
+
+/// MAX17215: multi-cell with balancing, 1-Wire
+pub struct Max17215;
+
+/// MAX17055: single cell, I2C, no nonvolatile memory
+pub struct Max17055;
+
+/// MAX17262: single cell with integrated sense resistor, I2C, no
+/// nonvolatile memory
+pub struct Max17262;
+
+/// MAX17301: single cell with protector, I2C
+pub struct Max17301;
+
+/// MAX17311: single cell with protector, 1-Wire
+pub struct Max17311;
+
+macro_rules! variant {
+    ($ty:ident, $name:literal, $cells:literal) => {
+        impl sealed::Sealed for $ty {}
+        impl Variant for $ty {
+            const NAME: &'static str = $name;
+            const MAX_CELLS: usize = $cells;
+        }
+    };
+}
+
+variant!(Generic, "MAX1720x", 4);
+variant!(Max17201, "MAX17201", 1);
+variant!(Max17205, "MAX17205", 4);
+variant!(Max17211, "MAX17211", 1);
+variant!(Max17215, "MAX17215", 4);
+variant!(Max17055, "MAX17055", 1);
+variant!(Max17262, "MAX17262", 1);
+variant!(Max17301, "MAX17301", 1);
+variant!(Max17311, "MAX17311", 1);
+
+impl Balancing for Generic {}
+impl Balancing for Max17205 {}
+impl Balancing for Max17215 {}
+
+impl NonVolatile for Generic {}
+impl NonVolatile for Max17201 {}
+impl NonVolatile for Max17205 {}
+impl NonVolatile for Max17211 {}
+impl NonVolatile for Max17215 {}
+
+impl PackMeasurement for Generic {}
+impl PackMeasurement for Max17201 {}
+impl PackMeasurement for Max17205 {}
+impl PackMeasurement for Max17211 {}
+impl PackMeasurement for Max17215 {}
+impl PackMeasurement for Max17301 {}
+impl PackMeasurement for Max17311 {}
+
+impl SmartBattery for Generic {}
+impl SmartBattery for Max17201 {}
+impl SmartBattery for Max17205 {}
+impl SmartBattery for Max17211 {}
+impl SmartBattery for Max17215 {}
+
+impl Protector for Max17301 {}
+impl Protector for Max17311 {}
@@ -0,0 +1,190 @@
+//! Low battery warning levels.
+//!
+//! [`WarningMonitor`] combines the state of charge, pack voltage and time to
+//! empty into a single [`BatteryWarning`] level.  Each quantity has a low
+//! and a critical threshold, and the worst level any of them indicates is
+//! reported.  Each quantity's level is raised as soon as it crosses a
+//! threshold but only cleared once it has recovered past the threshold by
+//! its hysteresis, so the warning doesn't flicker around a threshold:
+//!
+//! ```
+//! # use max1720x::mock::{MockError, MockMax1720x};
+//! # use max1720x::MAX1720x;
+//! # struct Delay;
+//! # impl embedded_hal::blocking::delay::DelayMs<u32> for Delay {
+//! #     fn delay_ms(&mut self, _ms: u32) {}
+//! # }
+//! # fn main() -> Result<(), max1720x::Error<MockError>> {
+//! # let mut i2c = MockMax1720x::new();
+//! # let mut delay = Delay;
+//! # let mut max17205: MAX1720x<_, _> = MAX1720x::new(&mut i2c);
+//! # max17205.init(&mut i2c)?;
+//! # use max1720x::warning::{BatteryWarning, WarningMonitor, WarningThresholds};
+//! # fn show_low_battery() {}
+//! # fn shut_down() {}
+//! let mut monitor = WarningMonitor::new(WarningThresholds {
+//!     voltage: Some((3.4, 3.2)),
+//!     ..Default::default()
+//! });
+//!
+//! match monitor.check(&mut max17205.attach(&mut i2c))? {
+//!     BatteryWarning::Ok => {}
+//!     BatteryWarning::Low => show_low_battery(),
+//!     BatteryWarning::Critical => shut_down(),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use core::time::Duration;
+
+use crate::gauge::FuelGauge;
+
+/// How close the battery is to empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum BatteryWarning {
+    /// No warning
+    #[default]
+    Ok,
+    /// The battery is low
+    Low,
+    /// The battery is about to run out
+    Critical,
+}
+
+/// Thresholds for each [`BatteryWarning`] level, each given as
+/// `(low, critical)`.  `None` leaves that quantity out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WarningThresholds {
+    /// State of charge thresholds as percentages
+    pub soc: Option<(f32, f32)>,
+    /// Pack voltage thresholds in volts
+    pub voltage: Option<(f32, f32)>,
+    /// Time to empty thresholds.  Ignored while the pack isn't discharging.
+    pub time_to_empty: Option<(Duration, Duration)>,
+    /// How far the state of charge must rise above a threshold, in percent,
+    /// to clear its level
+    pub soc_hysteresis: f32,
+    /// How far the pack voltage must rise above a threshold, in volts, to
+    /// clear its level
+    pub voltage_hysteresis: f32,
+    /// How far the time to empty must rise above a threshold to clear its
+    /// level
+    pub time_to_empty_hysteresis: Duration,
+}
+
+impl Default for WarningThresholds {
+    /// Low at 10% and critical at 5% state of charge, with 2% hysteresis.
+    /// The voltage and time to empty are left out, with hysteresis of
+    /// 50 mV and one minute.
+    fn default() -> Self {
+        WarningThresholds {
+            soc: Some((10.0, 5.0)),
+            voltage: None,
+            time_to_empty: None,
+            soc_hysteresis: 2.0,
+            voltage_hysteresis: 0.05,
+            time_to_empty_hysteresis: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The level indicated by one quantity, given the level it last indicated
+fn level(
+    value: f32,
+    (low, critical): (f32, f32),
+    hysteresis: f32,
+    current: BatteryWarning,
+) -> BatteryWarning {
+    let limit = |threshold: f32, level| {
+        if current >= level {
+            threshold + hysteresis
+        } else {
+            threshold
+        }
+    };
+    if value <= limit(critical, BatteryWarning::Critical) {
+        BatteryWarning::Critical
+    } else if value <= limit(low, BatteryWarning::Low) {
+        BatteryWarning::Low
+    } else {
+        BatteryWarning::Ok
+    }
+}
+
+/// Tracks the [`BatteryWarning`] level with hysteresis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WarningMonitor {
+    thresholds: WarningThresholds,
+    /// The level each quantity last indicated, for its hysteresis
+    soc_level: BatteryWarning,
+    voltage_level: BatteryWarning,
+    time_to_empty_level: BatteryWarning,
+}
+
+impl WarningMonitor {
+    /// Start monitoring with the given thresholds, at [`BatteryWarning::Ok`]
+    pub fn new(thresholds: WarningThresholds) -> Self {
+        WarningMonitor {
+            thresholds,
+            soc_level: BatteryWarning::Ok,
+            voltage_level: BatteryWarning::Ok,
+            time_to_empty_level: BatteryWarning::Ok,
+        }
+    }
+
+    /// The thresholds in use
+    pub fn thresholds(&self) -> &WarningThresholds {
+        &self.thresholds
+    }
+
+    /// The level from the last update
+    pub fn level(&self) -> BatteryWarning {
+        self.soc_level
+            .max(self.voltage_level)
+            .max(self.time_to_empty_level)
+    }
+
+    /// Work out the level from a new set of readings.  `time_to_empty` is
+    /// `None` while the pack isn't discharging.
+    pub fn update(
+        &mut self,
+        soc: f32,
+        voltage: f32,
+        time_to_empty: Option<Duration>,
+    ) -> BatteryWarning {
+        let t = &self.thresholds;
+        self.soc_level = match t.soc {
+            Some(thresholds) => level(soc, thresholds, t.soc_hysteresis, self.soc_level),
+            None => BatteryWarning::Ok,
+        };
+        self.voltage_level = match t.voltage {
+            Some(thresholds) => level(
+                voltage,
+                thresholds,
+                t.voltage_hysteresis,
+                self.voltage_level,
+            ),
+            None => BatteryWarning::Ok,
+        };
+        self.time_to_empty_level = match (t.time_to_empty, time_to_empty) {
+            (Some((low, critical)), Some(tte)) => level(
+                tte.as_secs_f32(),
+                (low.as_secs_f32(), critical.as_secs_f32()),
+                t.time_to_empty_hysteresis.as_secs_f32(),
+                self.time_to_empty_level,
+            ),
+            _ => BatteryWarning::Ok,
+        };
+        self.level()
+    }
+
+    /// Read the state of charge, pack voltage and time to empty from a
+    /// gauge and update the level
+    pub fn check<G: FuelGauge>(&mut self, gauge: &mut G) -> Result<BatteryWarning, G::Error> {
+        let soc = gauge.state_of_charge()?;
+        let voltage = gauge.voltage()?;
+        let time_to_empty = gauge.time_to_empty()?;
+        Ok(self.update(soc, voltage, time_to_empty))
+    }
+}